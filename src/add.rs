@@ -1,32 +1,151 @@
-//! Module for interactively adding recipes
-use crate::utils::{get_recipe_out_path, input_msg, print_table, title};
+//! Module for adding recipes, interactively or from arguments / files
+use crate::config::UnitSystem;
+use crate::errors::AddError;
+use crate::utils::{
+    check_name, confirm, get_recipe_out_path, input_msg, json_output, notify, notify_saved,
+    print_json, print_table, read_yaml, title,
+};
 use crate::{Ingredient, IngredientRow, Recipe, StepRow};
 use colored::*;
 use dialoguer::Input;
-use std::fs;
+use serde::Deserialize;
+use serde_yaml::Value;
 use std::io;
+use std::path::Path;
 use std::str::FromStr;
 
+/// Recipe as accepted by `--from-file`, ingredients may be written as
+/// `<AMOUNT> <UNIT> <INGREDIENT>` strings or as maps of `name`, `amount`, and `unit`
+#[derive(Debug, Deserialize)]
+struct RecipeFile {
+    name: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    servings: Option<u16>,
+    #[serde(default)]
+    ingredients: Vec<Value>,
+    #[serde(default)]
+    steps: Vec<String>,
+}
+
+/// Ingredient written as a map in a `--from-file` recipe
+#[derive(Debug, Deserialize)]
+struct IngredientFields {
+    name: String,
+    amount: f32,
+    unit: String,
+}
+
 /// Adds recipe to `recipe_dir` interactively.
 /// Displays a table of current ingredients / steps
-pub fn add_recipe(recipe_dir: &String) -> io::Result<()> {
+pub fn add_recipe(recipe_dir: &str, units: &UnitSystem, force: &bool) -> Result<(), AddError> {
+    if json_output() {
+        return Err(AddError::NoPrompt("use --name or --from-file"));
+    }
     title("\t\u{21F8} Recipe Name\n\n");
     let name: String = Input::new()
         .with_prompt("Enter recipe name")
         .interact_text()?;
-    let recipe_path = get_recipe_out_path(&recipe_dir, &name);
-    let tags = add_tags().expect("Failed to parse tags");
-    let ingredients = add_ingredients().expect("Failed adding ingredients");
-    let steps = add_steps().expect("Failed adding steps");
+    if !force
+        && get_recipe_out_path(recipe_dir, &name).exists()
+        && !confirm(&format!("{name} already exists, overwrite?"))?
+    {
+        return Err(AddError::AlreadyExists(name));
+    }
+    let tags = add_tags()?;
+    let ingredients = add_ingredients(units)?;
+    let steps = add_steps()?;
     let recipe = Recipe {
         name,
         tags,
+        servings: None,
         ingredients,
         steps,
     };
-    let serialized = serde_yaml::to_string(&recipe).expect("Failed to serialize recipe");
-    fs::write(&recipe_path, &serialized).expect("Failed to save recipe");
-    println!("Recipe saved to {}", recipe_path.to_str().unwrap());
+    save_recipe(&recipe, recipe_dir, &true)
+}
+
+/// Adds recipe to `recipe_dir` from command line arguments without prompting.
+/// Ingredients are parsed from `<AMOUNT> <UNIT> <INGREDIENT>` strings
+pub fn add_recipe_from_args(
+    recipe_dir: &str,
+    name: &str,
+    tags: &[String],
+    servings: &Option<u16>,
+    ingredients: &[String],
+    steps: &[String],
+    force: &bool,
+) -> Result<(), AddError> {
+    let recipe = Recipe {
+        name: name.trim().into(),
+        tags: tags.to_vec(),
+        servings: *servings,
+        ingredients: ingredients
+            .iter()
+            .map(|x| parse_ingredient(x))
+            .collect::<Result<Vec<Ingredient>, AddError>>()?,
+        steps: steps.to_vec(),
+    };
+    save_recipe(&recipe, recipe_dir, force)
+}
+
+/// Adds recipe to `recipe_dir` from a YAML recipe file, or stdin if `path` is `-`
+pub fn add_recipe_from_file(recipe_dir: &str, path: &str, force: &bool) -> Result<(), AddError> {
+    let file: RecipeFile = read_yaml(Path::new(path))?;
+    let recipe = Recipe {
+        name: file.name.trim().into(),
+        tags: file.tags,
+        servings: file.servings,
+        ingredients: file
+            .ingredients
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| parse_entry(i + 1, x))
+            .collect::<Result<Vec<Ingredient>, AddError>>()?,
+        steps: file.steps,
+    };
+    save_recipe(&recipe, recipe_dir, force)
+}
+
+/// Parses an ingredient, keeping the offending input on failure
+fn parse_ingredient(input: &str) -> Result<Ingredient, AddError> {
+    Ingredient::from_str(input).map_err(|e| AddError::InvalidIngredient(input.into(), e))
+}
+
+/// Parses the `n`th ingredient of a recipe file, maps are checked the same as strings
+fn parse_entry(n: usize, entry: Value) -> Result<Ingredient, AddError> {
+    match entry {
+        Value::String(text) => parse_ingredient(&text),
+        Value::Mapping(_) => {
+            let fields: IngredientFields = serde_yaml::from_value(entry)
+                .map_err(|e| AddError::InvalidEntry(n, e.to_string()))?;
+            parse_ingredient(&format!(
+                "{} {} {}",
+                fields.amount, fields.unit, fields.name
+            ))
+        }
+        _ => Err(AddError::InvalidEntry(
+            n,
+            "must be <AMOUNT> <UNIT> <INGREDIENT> or a map of name, amount, and unit".into(),
+        )),
+    }
+}
+
+/// Writes recipe to `recipe_dir`, refusing to overwrite an existing recipe unless `force`
+fn save_recipe(recipe: &Recipe, recipe_dir: &str, force: &bool) -> Result<(), AddError> {
+    if recipe.name.is_empty() {
+        return Err(AddError::NoName);
+    }
+    check_name(&recipe.name)?;
+    if !force && get_recipe_out_path(recipe_dir, &recipe.name).exists() {
+        return Err(AddError::AlreadyExists(recipe.name.clone()));
+    }
+    let recipe_path = recipe.write(recipe_dir)?;
+    notify_saved("Recipe", &recipe_path);
+    if json_output() {
+        print_json(recipe)?;
+    }
     Ok(())
 }
 
@@ -40,13 +159,14 @@ fn add_tags() -> io::Result<Vec<String>> {
 }
 
 /// Ask user to add ingredient with loop for bad input
-fn add_ingredients() -> io::Result<Vec<Ingredient>> {
+fn add_ingredients(units: &UnitSystem) -> io::Result<Vec<Ingredient>> {
     let base = "\t\u{21F8} Ingredients\n\n";
     let mut rows: Vec<IngredientRow> = vec![];
     let mut ingredients: Vec<Ingredient> = vec![];
     title(&format!(
-        "{}<AMOUNT> <UNIT> <INGREDIENT> (Ex: 1 lb beef)",
-        base
+        "{}<AMOUNT> <UNIT> <INGREDIENT> (Ex: {})",
+        base,
+        units.example_ingredient()
     ));
     loop {
         if !ingredients.is_empty() {
@@ -60,7 +180,7 @@ fn add_ingredients() -> io::Result<Vec<Ingredient>> {
             Ok(ingr) => ingr,
             Err(e) => {
                 title(&format!("{} (or ENTER to continue)", base));
-                println!("{e}\n{}\n", "...Please try again.".red());
+                notify(&format!("{e}\n{}\n", "...Please try again.".red()));
                 continue;
             }
         };
@@ -0,0 +1,288 @@
+//! Module for filling plans automatically under a set of rules
+//!
+//! Rules are set in `averse.toml` under `[auto]` or with `plan --auto` flags, e.g.
+//!
+//! ```toml
+//! [auto]
+//! slots = ["breakfast", "dinner"]          # slots filled on every day
+//! require = ["breakfast=tag:breakfast", "fri:dinner=tag:pizza"]
+//! max-repeats = 1                          # times a recipe may appear in the plan
+//! avoid-recent = 2                         # skip recipes from the latest N plans
+//! max-groceries = 30                       # cap on distinct groceries
+//! ```
+//!
+//! Requirements have the form `TARGET=QUERY`, where the target is a day, a meal slot, or
+//! `DAY:SLOT`, and the query uses the syntax of the `query` module. Days are weekday names,
+//! which apply to every such day of the plan, or dates.
+use crate::date::{parse_weekday, DATE_FORMAT};
+use crate::errors::PlanError;
+use crate::plan::{Meal, Meals, Slot};
+use crate::query::Query;
+use crate::Recipe;
+use chrono::{Datelike, NaiveDate, Weekday};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::str::FromStr;
+
+/// Number of random fills tried before giving up on the rules
+const ATTEMPTS: usize = 200;
+
+/// Rules for filling a plan, see the module docs
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Rules {
+    /// Meal slots filled on every day
+    pub slots: Vec<Slot>,
+    /// Requirements in the form `TARGET=QUERY`
+    pub require: Vec<String>,
+    /// Number of times a recipe may appear in the plan
+    pub max_repeats: usize,
+    /// Recipes planned in this many of the latest plans are skipped
+    pub avoid_recent: usize,
+    /// Maximum number of distinct groceries across the plan
+    pub max_groceries: Option<usize>,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            slots: vec![Slot::default()],
+            require: vec![],
+            max_repeats: 1,
+            avoid_recent: 0,
+            max_groceries: None,
+        }
+    }
+}
+
+/// Query recipes must match on some days and / or meal slots
+#[derive(Debug, Clone)]
+pub struct Requirement {
+    day: Option<Day>,
+    slot: Option<Slot>,
+    query: Query,
+}
+
+/// Day a requirement applies to
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Day {
+    Weekday(Weekday),
+    Date(NaiveDate),
+}
+
+impl Requirement {
+    /// Returns true if the requirement applies to a slot on a day
+    pub fn applies(&self, date: NaiveDate, slot: Slot) -> bool {
+        let day = match self.day {
+            Some(Day::Weekday(weekday)) => date.weekday() == weekday,
+            Some(Day::Date(day)) => date == day,
+            None => true,
+        };
+        day && self.slot.is_none_or(|x| x == slot)
+    }
+}
+
+impl FromStr for Requirement {
+    type Err = PlanError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || PlanError::InvalidRule(s.into());
+        let (target, query) = s.split_once('=').ok_or_else(invalid)?;
+        let query = query.parse::<Query>().map_err(|_| invalid())?;
+        let parse_day = |day: &str| match NaiveDate::parse_from_str(day.trim(), DATE_FORMAT) {
+            Ok(date) => Some(Day::Date(date)),
+            Err(_) => parse_weekday(day).map(Day::Weekday),
+        };
+        let (day, slot) = match target.split_once(':') {
+            Some((day, slot)) => (
+                Some(parse_day(day).ok_or_else(invalid)?),
+                Some(slot.parse()?),
+            ),
+            None => match target.parse::<Slot>() {
+                Ok(slot) => (None, Some(slot)),
+                Err(_) => (Some(parse_day(target).ok_or_else(invalid)?), None),
+            },
+        };
+        Ok(Requirement { day, slot, query })
+    }
+}
+
+/// Picks recipes for plans under a set of rules
+#[derive(Debug)]
+pub struct Generator {
+    recipes: Vec<Recipe>,
+    requirements: Vec<Requirement>,
+    rules: Rules,
+}
+
+impl Generator {
+    /// Creates a generator picking from `recipes`, skipping any with a slug in `recent`
+    pub fn new(
+        recipes: Vec<Recipe>,
+        recent: &HashSet<String>,
+        rules: &Rules,
+    ) -> Result<Self, PlanError> {
+        let recipes = recipes
+            .into_iter()
+            .filter(|x| !recent.contains(&x.slug()))
+            .collect::<Vec<Recipe>>();
+        if recipes.is_empty() {
+            return Err(PlanError::NoRecipes);
+        }
+        Ok(Generator {
+            recipes,
+            requirements: rules
+                .require
+                .iter()
+                .map(|x| x.parse())
+                .collect::<Result<Vec<Requirement>, PlanError>>()?,
+            rules: rules.clone(),
+        })
+    }
+
+    /// Fills every slot of every day
+    pub fn fill<R: Rng>(
+        &self,
+        rng: &mut R,
+        days: &[NaiveDate],
+    ) -> Result<BTreeMap<NaiveDate, Meals>, PlanError> {
+        self.fill_cells(rng, days, &BTreeMap::new(), &BTreeMap::new())
+    }
+
+    /// Picks new recipes for every slot of `day`, keeping the rest of the plan.
+    /// Recipes already planned on the day are avoided unless nothing else fits
+    pub fn swap<R: Rng>(
+        &self,
+        rng: &mut R,
+        day: NaiveDate,
+        recipes: &BTreeMap<NaiveDate, Meals>,
+    ) -> Result<BTreeMap<NaiveDate, Meals>, PlanError> {
+        let mut fixed = recipes.clone();
+        let current = fixed.remove(&day).unwrap_or_default();
+        self.fill_cells(rng, &[day], &fixed, &current)
+    }
+
+    /// Fills the slots of `days` around the `fixed` recipes, avoiding `exclude` in each slot
+    /// unless nothing else fits.
+    /// Recipes are picked at random, retrying until a fill satisfies every rule
+    fn fill_cells<R: Rng>(
+        &self,
+        rng: &mut R,
+        days: &[NaiveDate],
+        fixed: &BTreeMap<NaiveDate, Meals>,
+        exclude: &Meals,
+    ) -> Result<BTreeMap<NaiveDate, Meals>, PlanError> {
+        let cells = days
+            .iter()
+            .flat_map(|day| self.rules.slots.iter().map(move |slot| (*day, *slot)))
+            .collect::<Vec<(NaiveDate, Slot)>>();
+        let mut failed = None;
+        for _ in 0..ATTEMPTS {
+            match self.try_fill(rng, &cells, fixed, exclude) {
+                Ok(plan) => return Ok(plan),
+                Err(cell) => failed = Some(cell),
+            }
+        }
+        let (day, slot) = failed.unwrap_or((days[0], Slot::default()));
+        Err(PlanError::Unsatisfiable(day, slot))
+    }
+
+    /// Single random fill, returning the first cell no recipe fits on failure
+    fn try_fill<R: Rng>(
+        &self,
+        rng: &mut R,
+        cells: &[(NaiveDate, Slot)],
+        fixed: &BTreeMap<NaiveDate, Meals>,
+        exclude: &Meals,
+    ) -> Result<BTreeMap<NaiveDate, Meals>, (NaiveDate, Slot)> {
+        let mut plan = fixed.clone();
+        let mut fill = Fill::new(&self.recipes, &plan);
+        for (day, slot) in cells {
+            let excluded = exclude
+                .get(slot)
+                .iter()
+                .flat_map(|x| x.iter())
+                .map(|x| x.recipe().clone())
+                .collect::<Vec<String>>();
+            let (fresh, stale): (Vec<&Recipe>, Vec<&Recipe>) = self
+                .recipes
+                .iter()
+                .filter(|x| self.allowed(x, *day, *slot, &fill))
+                .partition(|x| !excluded.contains(&x.slug()));
+            let recipe = match fresh.is_empty() {
+                true => stale.choose(rng),
+                false => fresh.choose(rng),
+            }
+            .copied()
+            .ok_or((*day, *slot))?;
+            fill.add(recipe);
+            let meals = plan.entry(*day).or_default();
+            meals
+                .entry(*slot)
+                .or_default()
+                .push(Meal::Cook(recipe.slug()));
+        }
+        Ok(plan)
+    }
+
+    /// Returns true if a recipe may fill a slot on a day given the recipes picked so far
+    fn allowed(&self, recipe: &Recipe, day: NaiveDate, slot: Slot, fill: &Fill) -> bool {
+        let slug = recipe.slug();
+        let repeats = fill.counts.get(&slug).copied().unwrap_or(0);
+        let groceries = match self.rules.max_groceries {
+            Some(max) => fill.with_groceries(recipe) <= max,
+            None => true,
+        };
+        repeats < self.rules.max_repeats
+            && groceries
+            && self
+                .requirements
+                .iter()
+                .filter(|x| x.applies(day, slot))
+                .all(|x| x.query.matches(recipe))
+    }
+}
+
+/// Running totals of a fill in progress
+struct Fill {
+    /// Slug -> Times the recipe is planned
+    counts: HashMap<String, usize>,
+    /// Distinct groceries of the planned recipes
+    groceries: HashSet<String>,
+}
+
+impl Fill {
+    /// Totals for the recipes already in a plan
+    fn new(recipes: &[Recipe], plan: &BTreeMap<NaiveDate, Meals>) -> Self {
+        let mut fill = Fill {
+            counts: HashMap::new(),
+            groceries: HashSet::new(),
+        };
+        plan.values()
+            .flat_map(|x| x.values())
+            .flatten()
+            .filter(|x| !x.is_leftovers())
+            .filter_map(|meal| recipes.iter().find(|x| &x.slug() == meal.recipe()))
+            .for_each(|recipe| fill.add(recipe));
+        fill
+    }
+
+    fn add(&mut self, recipe: &Recipe) {
+        *self.counts.entry(recipe.slug()).or_insert(0) += 1;
+        self.groceries.extend(grocery_keys(recipe));
+    }
+
+    /// Number of distinct groceries if `recipe` were added
+    fn with_groceries(&self, recipe: &Recipe) -> usize {
+        let new = grocery_keys(recipe)
+            .filter(|x| !self.groceries.contains(x))
+            .collect::<HashSet<String>>();
+        self.groceries.len() + new.len()
+    }
+}
+
+/// Keys identifying distinct groceries, matching how plans compile their grocery lists
+fn grocery_keys(recipe: &Recipe) -> impl Iterator<Item = String> + '_ {
+    recipe.ingredients.iter().map(|x| x.key())
+}
@@ -1,46 +1,54 @@
 //! Module to BEHOLD your meal plan creations
-use crate::plan::Plan;
-use crate::utils::{get_jsons, get_recipe_out_path, print_table, select, title};
-use crate::{PlanRow, Recipe, RecipeParsingError};
-use std::path::Path;
+use crate::plan::{latest_plans, plan_table, Plan};
+use crate::utils::{get_recipe_out_path, json_output, print_json, select, title};
+use crate::{Recipe, RecipeParsingError};
+use chrono::{NaiveDate, Weekday};
 
-/// Logic to display plans
+/// Logic to display plans, either a single plan by name or the latest `n_plans`.
+/// Plans are shown one week per row, each week starting on `week_start`
 pub fn display_plan(
-    recipe_dir: &String,
-    plan_dir: &String,
+    recipe_dir: &str,
+    plan_dir: &str,
+    plan_date: &Option<NaiveDate>,
     n_plans: &usize,
+    week_start: &Weekday,
 ) -> Result<(), RecipeParsingError> {
     title("\t\u{21F8} Behold\n\n");
-    let plans = get_latest_plans(plan_dir, n_plans)?;
-    let rows: Vec<PlanRow> = plans.iter().map(|x| PlanRow::from(x.clone())).collect();
-    print_table(&rows);
+    let plans = match plan_date {
+        Some(date) => vec![Plan::load(date, recipe_dir, plan_dir)?],
+        None => latest_plans(plan_dir, n_plans)?,
+    };
+    if json_output() {
+        print_json(&plans)?;
+        return Ok(());
+    }
+    println!("{}", plan_table(&plans, *week_start));
 
     // Select Plan
-    let plan_names = plans
-        .iter()
-        .map(|x| x.name.clone())
-        .collect::<Vec<String>>();
-    let plan = &plans[select(&plan_names)?];
+    let plan_dates = plans.iter().map(|x| x.date).collect::<Vec<NaiveDate>>();
+    let plan = &plans[select(&plan_dates)?];
 
     // Select Day
-    let keys = plan.recipes.keys().cloned().collect::<Vec<String>>();
-    let day = &keys[select(&keys)?];
-    let recipe_names = plan.recipes.get(day).unwrap();
+    let days = plan.recipes.keys().collect::<Vec<&NaiveDate>>();
+    let labels = days
+        .iter()
+        .map(|x| x.format("%A %Y-%m-%d").to_string())
+        .collect::<Vec<String>>();
+    let day = *days[select(&labels)?];
 
     // Pick Recipe
-    let name = &recipe_names[select(recipe_names)?];
-    let recipe = Recipe::try_from(&get_recipe_out_path(&recipe_dir, &name))?;
+    let meals = plan
+        .meals()
+        .filter(|(date, _, _)| *date == day)
+        .collect::<Vec<_>>();
+    let labels = meals
+        .iter()
+        .map(|(_, slot, meal)| format!("{slot}: {meal}"))
+        .collect::<Vec<String>>();
+    let (_, _, meal) = meals[select(&labels)?];
+    let name = meal.recipe();
+    let recipe = Recipe::try_from(&get_recipe_out_path(recipe_dir, name))?;
     println!("{recipe}");
 
     Ok(())
 }
-
-/// Fetches latest N plans
-fn get_latest_plans(plan_dir: &str, n_plans: &usize) -> Result<Vec<Plan>, RecipeParsingError> {
-    let mut plan_paths = get_jsons(Path::new(&plan_dir))?;
-    if plan_paths.len() > *n_plans {
-        let idx = n_plans.min(&plan_paths.len()).clone();
-        plan_paths = plan_paths[..idx].to_vec();
-    }
-    plan_paths.iter().map(|x| Plan::try_from(x)).collect()
-}
@@ -0,0 +1,316 @@
+//! Module for sorting groceries into store categories
+//!
+//! Categories are guessed from keywords in ingredient names. Guesses are overridden, and the
+//! aisle order of grocery lists set, in a YAML file set by `category-file` in `averse.toml`,
+//! by default `categories.yaml` beside the config, e.g.
+//!
+//! ```yaml
+//! store: corner-market     # store whose aisle order is followed
+//! stores:                  # categories in the order of a store's aisles
+//!   corner-market: [produce, dairy, meat, pantry, spices, frozen]
+//!   warehouse: [pantry, frozen, meat, dairy, produce]
+//! ingredients:             # ingredient name -> category
+//!   tahini: pantry
+//!   feta: dairy
+//! ```
+//!
+//! A single listed store is followed without setting `store`. Categories missing from the
+//! store's order come last, and without a store the order of `Category::ALL` is followed.
+use crate::errors::RecipeParsingError;
+use crate::utils::read_yaml;
+use crate::Ingredient;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::path::Path;
+
+/// Store categories used to group grocery lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    Produce,
+    Meat,
+    Dairy,
+    Frozen,
+    Pantry,
+    Spices,
+    Other,
+}
+
+/// Keywords used to guess the category of an ingredient, checked in order.
+/// Matched as whole words or their plurals, see `contains_keyword`
+const KEYWORDS: [(Category, &[&str]); 6] = [
+    (Category::Frozen, &["frozen", "ice cream"]),
+    (
+        Category::Spices,
+        &[
+            "powder",
+            "dried",
+            "seasoning",
+            "cumin",
+            "paprika",
+            "oregano",
+            "pepper flakes",
+            "cinnamon",
+            "salt",
+            "black pepper",
+            "chili flakes",
+            "bay leaf",
+        ],
+    ),
+    (
+        Category::Pantry,
+        &[
+            "bean",
+            "rice",
+            "pasta",
+            "flour",
+            "sugar",
+            "oil",
+            "vinegar",
+            "sauce",
+            "stock",
+            "broth",
+            "paste",
+            "bread",
+            "bagel",
+            "tortilla",
+            "noodle",
+            "chips",
+            "honey",
+            "mustard",
+            "pickle",
+            "rigatoni",
+            "cocoa",
+            "vanilla",
+            "baking",
+            "chocolate",
+            "cracker",
+        ],
+    ),
+    (
+        Category::Meat,
+        &[
+            "beef",
+            "chicken",
+            "pork",
+            "sausage",
+            "chorizo",
+            "bacon",
+            "turkey",
+            "lamb",
+            "ham",
+            "pepperoni",
+            "fish",
+            "shrimp",
+            "salmon",
+            "steak",
+            "roast",
+        ],
+    ),
+    (
+        Category::Dairy,
+        &[
+            "cheese",
+            "milk",
+            "butter",
+            "cream",
+            "yogurt",
+            "egg",
+            "cheddar",
+            "mozzarella",
+            "parmesan",
+            "feta",
+            "ricotta",
+        ],
+    ),
+    (
+        Category::Produce,
+        &[
+            "onion",
+            "garlic",
+            "tomato",
+            "pepper",
+            "potato",
+            "carrot",
+            "celery",
+            "lettuce",
+            "parsley",
+            "cilantro",
+            "dill",
+            "lime",
+            "lemon",
+            "apple",
+            "banana",
+            "berry",
+            "avocado",
+            "jalapeno",
+            "spinach",
+            "broccoli",
+            "mushroom",
+            "corn",
+            "zucchini",
+            "cucumber",
+            "fruit",
+            "scallion",
+            "ginger",
+            "basil",
+            "eggplant",
+            "blueberry",
+            "strawberry",
+            "raspberry",
+        ],
+    ),
+];
+
+impl Category {
+    /// Every category, in the default aisle order
+    pub const ALL: [Category; 7] = [
+        Category::Produce,
+        Category::Meat,
+        Category::Dairy,
+        Category::Frozen,
+        Category::Pantry,
+        Category::Spices,
+        Category::Other,
+    ];
+
+    /// Guesses the category of an ingredient from keywords in its name
+    pub fn guess(ingredient: &str) -> Category {
+        let words = ingredient
+            .to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|x| !x.is_empty())
+            .map(String::from)
+            .collect::<Vec<String>>();
+        KEYWORDS
+            .iter()
+            .find(|(_, keywords)| keywords.iter().any(|k| contains_keyword(&words, k)))
+            .map(|(category, _)| *category)
+            .unwrap_or(Category::Other)
+    }
+}
+
+/// Whether the words of `keyword` appear in a row in `words`, so "salt" doesn't match
+/// "unsalted"
+fn contains_keyword(words: &[String], keyword: &str) -> bool {
+    let keyword = keyword.split(' ').collect::<Vec<&str>>();
+    words
+        .windows(keyword.len())
+        .any(|x| x.iter().zip(&keyword).all(|(word, k)| is_form_of(word, k)))
+}
+
+/// Whether `word` is `keyword` or its plural, e.g. eggs, tomatoes, berries, or leaves
+fn is_form_of(word: &str, keyword: &str) -> bool {
+    let plural = |singular: &str, plural: &str| {
+        keyword
+            .strip_suffix(singular)
+            .is_some_and(|stem| word == format!("{stem}{plural}"))
+    };
+    word == keyword
+        || plural("", "s")
+        || plural("", "es")
+        || plural("y", "ies")
+        || plural("f", "ves")
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match *self {
+            Category::Produce => write!(f, "Produce"),
+            Category::Meat => write!(f, "Meat"),
+            Category::Dairy => write!(f, "Dairy"),
+            Category::Frozen => write!(f, "Frozen"),
+            Category::Pantry => write!(f, "Pantry"),
+            Category::Spices => write!(f, "Spices"),
+            Category::Other => write!(f, "Other"),
+        }
+    }
+}
+
+/// Category overrides and aisle order, see the module docs for the file format
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(try_from = "CategoryFile")]
+pub struct Categories {
+    /// Categories in the order of the store's aisles
+    aisles: Vec<Category>,
+    /// Lowercase ingredient name -> Category
+    ingredients: BTreeMap<String, Category>,
+}
+
+/// Category file as written by users
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct CategoryFile {
+    store: Option<String>,
+    stores: BTreeMap<String, Vec<Category>>,
+    ingredients: BTreeMap<String, Category>,
+}
+
+impl Categories {
+    /// Loads the category file, falling back to guesses in the default order if there is none
+    pub fn load(path: &Path) -> Result<Self, RecipeParsingError> {
+        match path.exists() {
+            true => read_yaml(path),
+            false => Ok(Categories::default()),
+        }
+    }
+
+    /// Category of an ingredient, from the file or else guessed
+    pub fn category(&self, ingredient: &str) -> Category {
+        self.ingredients
+            .get(&ingredient.trim().to_lowercase())
+            .copied()
+            .unwrap_or_else(|| Category::guess(ingredient))
+    }
+
+    /// Every category in aisle order
+    pub fn aisles(&self) -> Vec<Category> {
+        let mut aisles = self.aisles.clone();
+        aisles.extend(Category::ALL.iter().filter(|x| !self.aisles.contains(x)));
+        aisles
+    }
+
+    /// Groups groceries by category in aisle order, sorted by name within each category
+    pub fn group<'a>(&self, groceries: &'a [Ingredient]) -> Vec<(Category, Vec<&'a Ingredient>)> {
+        let mut groups: BTreeMap<Category, Vec<&Ingredient>> = BTreeMap::new();
+        groceries.iter().for_each(|ingr| {
+            groups
+                .entry(self.category(&ingr.name))
+                .or_default()
+                .push(ingr)
+        });
+        self.aisles()
+            .into_iter()
+            .filter_map(|category| {
+                let mut items = groups.remove(&category)?;
+                items.sort_by_key(|x| x.name.to_lowercase());
+                Some((category, items))
+            })
+            .collect()
+    }
+}
+
+impl TryFrom<CategoryFile> for Categories {
+    type Error = String;
+    fn try_from(file: CategoryFile) -> Result<Self, Self::Error> {
+        let aisles = match (&file.store, file.stores.len()) {
+            (Some(store), _) => file
+                .stores
+                .get(store)
+                .cloned()
+                .ok_or_else(|| format!("store {store} is not listed under stores"))?,
+            (None, 1) => file.stores.into_values().next().unwrap_or_default(),
+            (None, 0) => vec![],
+            (None, _) => return Err("store must be set when more than one is listed".into()),
+        };
+        Ok(Categories {
+            aisles,
+            ingredients: file
+                .ingredients
+                .into_iter()
+                .map(|(name, category)| (name.trim().to_lowercase(), category))
+                .collect(),
+        })
+    }
+}
@@ -0,0 +1,118 @@
+//! Module for generating shell completions
+//!
+//! Besides the static subcommands and flags, arguments named `recipe`, `plan`, `template`, `tag`,
+//! and `without-tag` complete to the recipe slugs, plan dates, template names, and tags found on
+//! disk when the script is generated. Sourcing the script at shell startup keeps these values current, e.g.
+//! `source <(averse completions bash)`
+use crate::utils::{get_jsons, load_recipes};
+use crate::RecipeParsingError;
+use clap::Command;
+use clap_complete::{generate, Shell};
+use std::collections::BTreeSet;
+use std::io;
+use std::path::Path;
+
+/// Values completed for arguments that refer to files on disk
+#[derive(Debug, Default)]
+pub struct DynamicValues {
+    pub recipes: Vec<&'static str>,
+    pub plans: Vec<&'static str>,
+    pub templates: Vec<&'static str>,
+    pub tags: Vec<&'static str>,
+}
+
+/// Logic for printing a completion script for `shell` to stdout
+pub fn print_completions(
+    cmd: Command<'static>,
+    shell: Shell,
+    recipe_dir: &str,
+    plan_dir: &str,
+    template_dir: &str,
+) -> Result<(), RecipeParsingError> {
+    let values = DynamicValues::load(recipe_dir, plan_dir, template_dir)?;
+    let mut cmd = with_dynamic_values(cmd, &values);
+    let name = cmd.get_name().to_string();
+    generate(shell, &mut cmd, name, &mut io::stdout());
+    Ok(())
+}
+
+impl DynamicValues {
+    /// Collects recipe slugs, plan dates, template names, and tags,
+    /// missing directories complete to nothing
+    pub fn load(
+        recipe_dir: &str,
+        plan_dir: &str,
+        template_dir: &str,
+    ) -> Result<Self, RecipeParsingError> {
+        if !Path::new(recipe_dir).is_dir() {
+            return Ok(DynamicValues::default());
+        }
+        let tags = load_recipes(recipe_dir)?
+            .iter()
+            .flat_map(|x| x.tags.clone())
+            .collect::<BTreeSet<String>>();
+        Ok(DynamicValues {
+            recipes: leak(file_stems(recipe_dir)?),
+            plans: leak(file_stems(plan_dir)?),
+            templates: leak(file_stems(template_dir)?),
+            tags: leak(tags),
+        })
+    }
+
+    /// Values completed for an argument id, if it refers to files on disk
+    fn get(&self, id: &str) -> Option<&Vec<&'static str>> {
+        match id {
+            "recipe" => Some(&self.recipes),
+            "plan" => Some(&self.plans),
+            "template" => Some(&self.templates),
+            "tag" | "without-tag" => Some(&self.tags),
+            _ => None,
+        }
+    }
+}
+
+/// Sets possible values on matching arguments of a command and all of its subcommands.
+/// Only used for generating completions, as the values would otherwise restrict parsing
+pub fn with_dynamic_values(mut cmd: Command<'static>, values: &DynamicValues) -> Command<'static> {
+    let ids = cmd
+        .get_arguments()
+        .filter(|x| x.is_takes_value_set())
+        .map(|x| x.get_id())
+        .collect::<Vec<&str>>();
+    for id in ids {
+        if let Some(possible) = values.get(id) {
+            if !possible.is_empty() {
+                cmd = cmd.mut_arg(id, |x| x.possible_values(possible.clone()));
+            }
+        }
+    }
+    let names = cmd
+        .get_subcommands()
+        .map(|x| x.get_name().to_string())
+        .collect::<Vec<String>>();
+    for name in names {
+        cmd = cmd.mut_subcommand(name.as_str(), |x| with_dynamic_values(x, values));
+    }
+    cmd
+}
+
+/// Sorted file names without extensions, e.g. recipe slugs or plan dates.
+/// A missing directory has none
+fn file_stems(dir: &str) -> Result<BTreeSet<String>, RecipeParsingError> {
+    if !Path::new(dir).is_dir() {
+        return Ok(BTreeSet::new());
+    }
+    Ok(get_jsons(Path::new(dir))?
+        .iter()
+        .filter_map(|x| x.file_stem())
+        .map(|x| x.to_string_lossy().to_string())
+        .collect())
+}
+
+/// Completion scripts are generated once per process, so values are leaked to satisfy clap
+fn leak(values: BTreeSet<String>) -> Vec<&'static str> {
+    values
+        .into_iter()
+        .map(|x| &*Box::leak(x.into_boxed_str()))
+        .collect()
+}
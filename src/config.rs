@@ -0,0 +1,196 @@
+//! Module for loading settings from an `averse.toml` config file
+//!
+//! The config is found by walking up from the current directory, falling back to
+//! `$XDG_CONFIG_HOME/averse/averse.toml` (or `~/.config/averse/averse.toml`).
+//! Every key is optional and command line flags take precedence, e.g.
+//!
+//! ```toml
+//! recipe-dir = "recipes"   # relative paths are resolved from the config file
+//! plan-dir = "plans"
+//! template-dir = "templates"
+//! units = "metric"         # imperial or metric
+//! week-start = "monday"  # first day of new plans and of week rows
+//! n-plans = 3              # plans shown by `behold`
+//! household = 4            # people eating each meal, used to suggest leftovers
+//! pantry-file = "pantry.yaml"        # ingredients on hand
+//! category-file = "categories.yaml"  # grocery categories and store aisles
+//! color = "never"          # auto, always, or never
+//! ```
+//!
+//! The pantry and category files are described in [`crate::pantry`] and [`crate::category`].
+//! Rules for `plan --auto` are set in an `[auto]` table, see [`crate::auto`].
+use crate::auto::Rules;
+use crate::errors::ConfigError;
+use crate::utils::read_input;
+use chrono::Weekday;
+use serde::{Deserialize, Deserializer};
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// Name of the config file searched for
+pub const CONFIG_FILE: &str = "averse.toml";
+
+/// User settings, see the module docs for the file format
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    /// Path to recipe directory
+    pub recipe_dir: String,
+    /// Path to plans directory
+    pub plan_dir: String,
+    /// Path to plan templates directory
+    pub template_dir: String,
+    /// Unit system suggested when entering ingredients
+    pub units: UnitSystem,
+    /// First day of the week, used for the default plan date and `behold` columns
+    #[serde(deserialize_with = "deserialize_weekday")]
+    pub week_start: Weekday,
+    /// Number of plans displayed by `behold`
+    pub n_plans: usize,
+    /// Number of people eating each meal
+    pub household: u16,
+    /// Path to the pantry file
+    pub pantry_file: String,
+    /// Path to the grocery category file
+    pub category_file: String,
+    /// Whether output is colored
+    pub color: ColorChoice,
+    /// Rules for filling plans automatically
+    pub auto: Rules,
+}
+
+/// Settings of the household plans are made for, see `Config::household`
+#[derive(Debug, Clone)]
+pub struct Household {
+    /// Number of people eating each meal
+    pub size: u16,
+    /// Path to the pantry file, see [`crate::pantry`]
+    pub pantry_file: PathBuf,
+    /// Path to the grocery category file, see [`crate::category`]
+    pub category_file: PathBuf,
+}
+
+impl Default for Household {
+    fn default() -> Self {
+        Config::default().household()
+    }
+}
+
+/// Preferred system of measurement
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnitSystem {
+    Imperial,
+    Metric,
+}
+
+/// When to color output
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+    /// Color if the terminal supports it
+    Auto,
+    Always,
+    Never,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            recipe_dir: "./recipes".into(),
+            plan_dir: "./plans".into(),
+            template_dir: "./templates".into(),
+            units: UnitSystem::Imperial,
+            week_start: Weekday::Sun,
+            n_plans: 5,
+            household: 2,
+            pantry_file: "./pantry.yaml".into(),
+            category_file: "./categories.yaml".into(),
+            color: ColorChoice::Auto,
+            auto: Rules::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Loads the nearest config file, or the defaults if there is none
+    pub fn load() -> Result<Self, ConfigError> {
+        match find_config(&env::current_dir()?) {
+            Some(path) => Config::from_path(&path),
+            None => Ok(Config::default()),
+        }
+    }
+
+    /// Loads a config file, resolving relative directories from the file's location
+    pub fn from_path(path: &Path) -> Result<Self, ConfigError> {
+        let mut config: Config = toml::from_str(&read_input(path)?)
+            .map_err(|e| ConfigError::DeserializeError(path.into(), e))?;
+        let root = path.parent().unwrap_or_else(|| Path::new("."));
+        config.recipe_dir = resolve(root, &config.recipe_dir);
+        config.plan_dir = resolve(root, &config.plan_dir);
+        config.template_dir = resolve(root, &config.template_dir);
+        config.pantry_file = resolve(root, &config.pantry_file);
+        config.category_file = resolve(root, &config.category_file);
+        Ok(config)
+    }
+
+    /// Settings of the household plans are made for
+    pub fn household(&self) -> Household {
+        Household {
+            size: self.household,
+            pantry_file: PathBuf::from(&self.pantry_file),
+            category_file: PathBuf::from(&self.category_file),
+        }
+    }
+
+    /// Applies the color preference to all terminal output
+    pub fn apply_color(&self) {
+        let enabled = match self.color {
+            ColorChoice::Auto => return,
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        };
+        colored::control::set_override(enabled);
+        console::set_colors_enabled(enabled);
+        console::set_colors_enabled_stderr(enabled);
+    }
+}
+
+impl UnitSystem {
+    /// Example ingredient shown when prompting for ingredients
+    pub fn example_ingredient(&self) -> &'static str {
+        match self {
+            UnitSystem::Imperial => "1 lb beef",
+            UnitSystem::Metric => "500 gram beef",
+        }
+    }
+}
+
+/// Finds `averse.toml` in `start` or one of its ancestors, then in the user config directory
+pub fn find_config(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(CONFIG_FILE))
+        .chain(user_config_dir().map(|dir| dir.join("averse").join(CONFIG_FILE)))
+        .find(|path| path.is_file())
+}
+
+/// `$XDG_CONFIG_HOME`, or `~/.config` if unset
+fn user_config_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|x| !x.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|x| Path::new(&x).join(".config")))
+}
+
+/// Joins a relative path onto `root`, leaving absolute paths as-is
+fn resolve(root: &Path, path: &str) -> String {
+    root.join(path).to_string_lossy().to_string()
+}
+
+/// Parses a weekday such as `mon` or `Monday`
+fn deserialize_weekday<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Weekday, D::Error> {
+    let day = String::deserialize(deserializer)?;
+    day.parse::<Weekday>()
+        .map_err(|_| serde::de::Error::custom(format!("invalid week-start: {day}")))
+}
@@ -0,0 +1,79 @@
+//! Module for parsing plan dates
+//!
+//! Dates may be given as:
+//! - `YEAR-MONTH-DAY`      - e.g. 2022-07-31
+//! - `today`, `tomorrow`
+//! - `WEEKDAY`             - The next such day on or after today, e.g. `sunday` or `sun`
+//! - `next-WEEKDAY`        - The next such day after today, e.g. `next-sunday`
+//! - `+N[d|w]`, `-N[d|w]`  - Offset from today in days or weeks, e.g. `+1w`
+use crate::errors::DateError;
+use chrono::{Datelike, Duration, Local, NaiveDate, Weekday};
+
+/// Format of dates in plan names and files
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Current local date
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// Parses an absolute or relative date, see the module docs for accepted forms
+pub fn parse_date(input: &str, today: NaiveDate) -> Result<NaiveDate, DateError> {
+    let invalid = || DateError::InvalidDate(input.into());
+    let value = input.trim().to_lowercase();
+    if let Ok(date) = NaiveDate::parse_from_str(&value, DATE_FORMAT) {
+        return Ok(date);
+    }
+    match value.as_str() {
+        "today" => return Ok(today),
+        "tomorrow" => return Ok(today + Duration::days(1)),
+        _ => (),
+    }
+    if let Some(day) = value.strip_prefix("next-") {
+        let weekday = parse_weekday(day).ok_or_else(invalid)?;
+        return Ok(next_weekday(today + Duration::days(1), weekday));
+    }
+    if let Some(weekday) = parse_weekday(&value) {
+        return Ok(next_weekday(today, weekday));
+    }
+    let (sign, offset) = match value.split_at(value.len().min(1)) {
+        ("+", offset) => (1, offset),
+        ("-", offset) => (-1, offset),
+        _ => return Err(invalid()),
+    };
+    let (n, unit) = offset.split_at(offset.len().saturating_sub(1));
+    let n = n.parse::<i64>().map_err(|_| invalid())? * sign;
+    let offset = match unit {
+        "d" => Duration::try_days(n),
+        "w" => Duration::try_weeks(n),
+        _ => None,
+    };
+    offset
+        .and_then(|x| today.checked_add_signed(x))
+        .ok_or_else(invalid)
+}
+
+/// Parses a date relative to today, for use as a clap argument parser
+pub fn parse_date_arg(input: &str) -> Result<NaiveDate, DateError> {
+    parse_date(input, today())
+}
+
+/// First day of the upcoming week, which may be today
+pub fn upcoming_week(week_start: Weekday, today: NaiveDate) -> NaiveDate {
+    next_weekday(today, week_start)
+}
+
+/// First date on or after `date` falling on `weekday`
+pub fn next_weekday(date: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let offset = (7 + weekday.num_days_from_sunday() - date.weekday().num_days_from_sunday()) % 7;
+    date + Duration::days(offset as i64)
+}
+
+/// Parses a weekday such as `mon` or `Monday`.
+/// Also accepts `Tuedsay`, which older versions wrote to plan files
+pub fn parse_weekday(day: &str) -> Option<Weekday> {
+    match day.trim().to_lowercase().as_str() {
+        "tuedsay" => Some(Weekday::Tue),
+        day => day.parse::<Weekday>().ok(),
+    }
+}
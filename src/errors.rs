@@ -1,21 +1,377 @@
 //! Minimal error definitions using `thiserror`
+//!
+//! Every error maps to an `ErrorCategory`, which decides the exit code of the binary
+use crate::plan::Slot;
 use crate::UNITS;
+use chrono::NaiveDate;
+use std::io;
+use std::path::PathBuf;
 use thiserror::Error;
 
+/// Broad kind of error, each exits with its own code so scripts can tell them apart
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCategory {
+    /// A prompt was cancelled with Esc or Ctrl-C
+    Cancelled,
+    /// An argument or input value is invalid, e.g. an unknown day
+    Input,
+    /// A recipe, plan, or file does not exist
+    NotFound,
+    /// A file exists but could not be parsed
+    Parse,
+    /// Reading or writing failed
+    Io,
+    /// The `averse.toml` config is invalid
+    Config,
+}
+
+impl ErrorCategory {
+    /// Exit code of the binary, 2 is left to clap for usage errors
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ErrorCategory::Input => 3,
+            ErrorCategory::NotFound => 4,
+            ErrorCategory::Parse => 5,
+            ErrorCategory::Io => 6,
+            ErrorCategory::Config => 7,
+            ErrorCategory::Cancelled => 130,
+        }
+    }
+}
+
+/// Categorizes an I/O error, terminal prompts report cancellation as `Interrupted`
+fn io_category(e: &io::Error) -> ErrorCategory {
+    match e.kind() {
+        io::ErrorKind::Interrupted => ErrorCategory::Cancelled,
+        io::ErrorKind::NotFound => ErrorCategory::NotFound,
+        _ => ErrorCategory::Io,
+    }
+}
+
+/// Any error returned by a subcommand
+#[derive(Debug, Error)]
+pub enum AverseError {
+    #[error(transparent)]
+    RecipeError(#[from] RecipeParsingError),
+    #[error(transparent)]
+    AddError(#[from] AddError),
+    #[error(transparent)]
+    PlanError(#[from] PlanError),
+    #[error(transparent)]
+    ExportError(#[from] ExportError),
+    #[error(transparent)]
+    ImportError(#[from] ImportError),
+    #[error(transparent)]
+    ShowError(#[from] ShowError),
+    #[error(transparent)]
+    TemplateError(#[from] TemplateError),
+    #[error(transparent)]
+    PantryError(#[from] PantryError),
+    #[error(transparent)]
+    ConfigError(#[from] ConfigError),
+}
+
+impl AverseError {
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            AverseError::RecipeError(e) => e.category(),
+            AverseError::AddError(e) => e.category(),
+            AverseError::PlanError(e) => e.category(),
+            AverseError::ExportError(e) => e.category(),
+            AverseError::ImportError(e) => e.category(),
+            AverseError::ShowError(e) => e.category(),
+            AverseError::TemplateError(e) => e.category(),
+            AverseError::PantryError(e) => e.category(),
+            AverseError::ConfigError(_) => ErrorCategory::Config,
+        }
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum RecipeParsingError {
-    #[error("Failed to read/write recipe file")]
-    IOError(#[from] std::io::Error),
-    #[error("Failed to serialize/deserialize recipe file")]
+    #[error("Failed to read/write recipe file: {0}")]
+    IOError(#[from] io::Error),
+    #[error("Failed to serialize/deserialize recipe file: {0}")]
     DeserializeError(#[from] serde_yaml::Error),
+    #[error("{} does not exist", .0.display())]
+    NotFound(PathBuf),
+    #[error("Failed to read/write {}: {1}", .0.display())]
+    FileError(PathBuf, io::Error),
+    #[error("Failed to parse {}: {1}", .0.display())]
+    InvalidFile(PathBuf, serde_yaml::Error),
+    #[error("{0:?} is not a valid name - it can't contain /, \\, or ..")]
+    InvalidName(String),
+}
+
+impl RecipeParsingError {
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            RecipeParsingError::IOError(e) | RecipeParsingError::FileError(_, e) => io_category(e),
+            RecipeParsingError::NotFound(_) => ErrorCategory::NotFound,
+            RecipeParsingError::InvalidName(_) => ErrorCategory::Input,
+            RecipeParsingError::DeserializeError(_) | RecipeParsingError::InvalidFile(..) => {
+                ErrorCategory::Parse
+            }
+        }
+    }
 }
 
 #[derive(Debug, Error)]
 pub enum IngredientParsingError {
     #[error("AMOUNT must be a valid number")]
     InvalidAmount(#[from] std::num::ParseFloatError),
+    #[error("AMOUNT must be zero or more, not {0}")]
+    OutOfRange(f32),
     #[error("{0} invalid UNIT - must be one of: {UNITS:?}")]
     InvalidUnit(String),
     #[error("No ingredient provided")]
     NoIngredient,
 }
+
+#[derive(Debug, Error)]
+pub enum AddError {
+    #[error("Failed to read/write recipe")]
+    IOError(#[from] io::Error),
+    #[error(transparent)]
+    RecipeError(#[from] RecipeParsingError),
+    #[error("Failed to deserialize recipe file: {0}")]
+    DeserializeError(#[from] serde_yaml::Error),
+    #[error("{0:?} - {1}")]
+    InvalidIngredient(String, IngredientParsingError),
+    #[error("Ingredient {0} - {1}")]
+    InvalidEntry(usize, String),
+    #[error("No recipe name provided")]
+    NoName,
+    #[error("{0} already exists - use --force to overwrite")]
+    AlreadyExists(String),
+    #[error("--json can't be used with prompts - {0}")]
+    NoPrompt(&'static str),
+}
+
+impl AddError {
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            AddError::IOError(e) => io_category(e),
+            AddError::RecipeError(e) => e.category(),
+            AddError::DeserializeError(_) => ErrorCategory::Parse,
+            _ => ErrorCategory::Input,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PlanError {
+    #[error("Failed to read/write plan")]
+    IOError(#[from] io::Error),
+    #[error(transparent)]
+    RecipeError(#[from] RecipeParsingError),
+    #[error("Failed to deserialize plan file: {0}")]
+    DeserializeError(#[from] serde_yaml::Error),
+    #[error("{0:?} is not a valid meal - must be DAY[:SLOT]=RECIPE e.g. Monday=chili")]
+    InvalidMeal(String),
+    #[error("{0} is not a meal slot - must be breakfast, lunch, dinner, or snack")]
+    InvalidSlot(String),
+    #[error("{0:?} is not a valid rule - must be TARGET=QUERY e.g. fri=tag:pizza or breakfast=tag:breakfast")]
+    InvalidRule(String),
+    #[error("No recipe fits {1} on {0} under the auto rules, try loosening them")]
+    Unsatisfiable(NaiveDate, Slot),
+    #[error("{0} is not a day of the plan")]
+    InvalidDay(String),
+    #[error("Plan can't end on {1} before it starts on {0}")]
+    InvalidSpan(NaiveDate, NaiveDate),
+    #[error("{0} is not a recipe in the recipe directory")]
+    UnknownRecipe(String),
+    #[error("{0} on {1} is planned as leftovers, but isn't cooked earlier in the plan")]
+    NoCook(String, NaiveDate),
+    #[error(transparent)]
+    DateError(#[from] DateError),
+    #[error("Can't copy the plan of {0} onto itself - pick another --date")]
+    CopyOntoSelf(NaiveDate),
+    #[error("No date provided for the plan")]
+    NoDate,
+    #[error("No recipes match the query")]
+    NoRecipes,
+    #[error("No plans match the given dates")]
+    NoPlans,
+    #[error("No recipes match the template query for {1} on {0}")]
+    NoMatch(NaiveDate, Slot),
+    #[error(transparent)]
+    QueryError(#[from] QueryError),
+    #[error("--json can't be used with prompts - {0}")]
+    NoPrompt(&'static str),
+}
+
+impl PlanError {
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            PlanError::IOError(e) => io_category(e),
+            PlanError::RecipeError(e) => e.category(),
+            PlanError::DeserializeError(_) => ErrorCategory::Parse,
+            PlanError::UnknownRecipe(_)
+            | PlanError::NoRecipes
+            | PlanError::NoPlans
+            | PlanError::NoMatch(..)
+            | PlanError::Unsatisfiable(..) => ErrorCategory::NotFound,
+            _ => ErrorCategory::Input,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum DateError {
+    #[error("{0} is not a valid date - must be YEAR-MONTH-DAY, a weekday e.g. next-sunday, or an offset e.g. +1w")]
+    InvalidDate(String),
+}
+
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error(transparent)]
+    RecipeError(#[from] RecipeParsingError),
+    #[error("Failed to write export file")]
+    IOError(#[from] io::Error),
+    #[error("Failed to serialize export file")]
+    SerializeError(#[from] serde_yaml::Error),
+}
+
+impl ExportError {
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            ExportError::RecipeError(e) => e.category(),
+            ExportError::IOError(e) => io_category(e),
+            ExportError::SerializeError(_) => ErrorCategory::Parse,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ImportError {
+    #[error("Failed to read/write import file")]
+    IOError(#[from] io::Error),
+    #[error("Failed to parse CSV row: {0}")]
+    CsvError(#[from] csv::Error),
+    #[error("Failed to deserialize import file: {0}")]
+    DeserializeError(#[from] serde_yaml::Error),
+    #[error(transparent)]
+    RecipeError(#[from] RecipeParsingError),
+    #[error(transparent)]
+    IngredientError(#[from] IngredientParsingError),
+    #[error("No recipe name provided")]
+    NoName,
+    #[error("{0} already exists")]
+    AlreadyExists(String),
+}
+
+impl ImportError {
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            ImportError::IOError(e) => io_category(e),
+            ImportError::RecipeError(e) => e.category(),
+            ImportError::AlreadyExists(_) => ErrorCategory::Input,
+            _ => ErrorCategory::Parse,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum TemplateError {
+    #[error("Failed to read/write template")]
+    IOError(#[from] io::Error),
+    #[error(transparent)]
+    RecipeError(#[from] RecipeParsingError),
+    #[error("Failed to serialize template")]
+    SerializeError(#[from] serde_yaml::Error),
+    #[error("Template {0} already exists - use --force to overwrite")]
+    AlreadyExists(String),
+}
+
+impl TemplateError {
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            TemplateError::IOError(e) => io_category(e),
+            TemplateError::RecipeError(e) => e.category(),
+            TemplateError::SerializeError(_) => ErrorCategory::Parse,
+            TemplateError::AlreadyExists(_) => ErrorCategory::Input,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PantryError {
+    #[error("Failed to read/write pantry")]
+    IOError(#[from] io::Error),
+    #[error(transparent)]
+    RecipeError(#[from] RecipeParsingError),
+    #[error(transparent)]
+    IngredientError(#[from] IngredientParsingError),
+    #[error("{0:?} - {1}")]
+    InvalidIngredient(String, IngredientParsingError),
+    #[error("{0} is not in the pantry")]
+    NotInPantry(String),
+}
+
+impl PantryError {
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            PantryError::IOError(e) => io_category(e),
+            PantryError::RecipeError(e) => e.category(),
+            PantryError::NotInPantry(_) => ErrorCategory::NotFound,
+            _ => ErrorCategory::Input,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum QueryError {
+    #[error("Query has a \" without a closing \"")]
+    UnterminatedQuote,
+    #[error("{0}: is not a valid field - must be one of: tag, ingredient, name, step")]
+    UnknownField(String),
+    #[error("{0}: must be followed by a value")]
+    EmptyValue(String),
+    #[error("OR must be placed between two terms")]
+    DanglingOr,
+}
+
+#[derive(Debug, Error)]
+pub enum ShowError {
+    #[error(transparent)]
+    RecipeError(#[from] RecipeParsingError),
+    #[error("No recipe named {0}{}", did_you_mean(.1))]
+    NotFound(String, Vec<String>),
+    #[error("{0} matches several recipes: {}", .1.join(", "))]
+    Ambiguous(String, Vec<String>),
+    #[error("{0} does not list servings - use --scale instead")]
+    NoServings(String),
+    #[error("Can't scale a recipe by {0} - the scale must be a number more than zero")]
+    InvalidScale(f32),
+}
+
+impl ShowError {
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            ShowError::RecipeError(e) => e.category(),
+            ShowError::NotFound(..) => ErrorCategory::NotFound,
+            ShowError::Ambiguous(..) | ShowError::NoServings(_) | ShowError::InvalidScale(_) => {
+                ErrorCategory::Input
+            }
+        }
+    }
+}
+
+/// Formats suggestions for a recipe that could not be found
+fn did_you_mean(suggestions: &[String]) -> String {
+    match suggestions.is_empty() {
+        true => String::new(),
+        false => format!(" - did you mean: {}?", suggestions.join(", ")),
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("Failed to read config: {0}")]
+    IOError(#[from] io::Error),
+    #[error(transparent)]
+    FileError(#[from] RecipeParsingError),
+    #[error("Failed to parse {}: {1}", .0.display())]
+    DeserializeError(PathBuf, toml::de::Error),
+}
@@ -0,0 +1,136 @@
+//! Module for exporting meal plans to other formats
+use crate::errors::ExportError;
+use crate::orf::OrfRecipe;
+use crate::plan::Plan;
+use crate::utils::{get_recipe_out_path, report_saved, write_file};
+use crate::Recipe;
+use chrono::{Duration, NaiveDate, Utc};
+use std::path::Path;
+
+/// Maximum length of an iCalendar content line in octets (RFC 5545 3.1)
+const ICS_LINE_LIMIT: usize = 75;
+
+/// Logic for exporting a plan to an iCalendar file
+pub fn export_ics(
+    recipe_dir: &str,
+    plan_dir: &str,
+    plan_date: &NaiveDate,
+    output: &Option<String>,
+) -> Result<(), ExportError> {
+    let plan = Plan::load(plan_date, recipe_dir, plan_dir)?;
+    let calendar = plan_to_ics(&plan, recipe_dir)?;
+    let outpath = match output {
+        Some(path) => Path::new(path).to_path_buf(),
+        None => Path::new(&plan.date.to_string()).with_extension("ics"),
+    };
+    write_file(&outpath, &calendar)?;
+    report_saved("Calendar", &outpath)?;
+    Ok(())
+}
+
+/// Logic for exporting a recipe to an Open Recipe Format file
+pub fn export_orf(
+    recipe_dir: &str,
+    recipe_name: &str,
+    output: &Option<String>,
+) -> Result<(), ExportError> {
+    let recipe = Recipe::try_from(&get_recipe_out_path(recipe_dir, recipe_name))?;
+    let outpath = match output {
+        Some(path) => Path::new(path).to_path_buf(),
+        None => Path::new(&recipe.name.replace(' ', "-")).with_extension("orf.yaml"),
+    };
+    write_file(&outpath, &serde_yaml::to_string(&OrfRecipe::from(&recipe))?)?;
+    report_saved("Recipe", &outpath)?;
+    Ok(())
+}
+
+/// Converts a plan into an iCalendar document with one all-day event per recipe and meal slot
+pub fn plan_to_ics(plan: &Plan, recipe_dir: &str) -> Result<String, ExportError> {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ");
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//averse//Meal Planner//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    for (date, slot, meal) in plan.meals() {
+        let name = meal.recipe();
+        let recipe = Recipe::try_from(&get_recipe_out_path(recipe_dir, name))?;
+        let summary = match meal.is_leftovers() {
+            true => format!("{slot}: {} (leftovers)", recipe.name.replace('-', " ")),
+            false => format!("{slot}: {}", recipe.name.replace('-', " ")),
+        };
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!(
+                "UID:{}-{}-{}{}@averse",
+                date.format("%Y%m%d"),
+                slot.to_string().to_lowercase(),
+                if meal.is_leftovers() {
+                    "leftovers-"
+                } else {
+                    ""
+                },
+                name
+            ),
+            format!("DTSTAMP:{stamp}"),
+            format!("DTSTART;VALUE=DATE:{}", date.format("%Y%m%d")),
+            format!(
+                "DTEND;VALUE=DATE:{}",
+                (date + Duration::days(1)).format("%Y%m%d")
+            ),
+            format!("SUMMARY:{}", escape_text(&summary)),
+            format!("DESCRIPTION:{}", escape_text(&describe(&recipe))),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    Ok(lines
+        .iter()
+        .map(|line| fold_line(line))
+        .collect::<Vec<String>>()
+        .join("\r\n")
+        + "\r\n")
+}
+
+/// Plain-text event description holding ingredients and steps
+fn describe(recipe: &Recipe) -> String {
+    let ingredients = recipe
+        .ingredients
+        .iter()
+        .map(|x| format!("- {x}"))
+        .collect::<Vec<String>>()
+        .join("\n");
+    let steps = recipe
+        .steps
+        .iter()
+        .enumerate()
+        .map(|(i, x)| format!("{}. {x}", i + 1))
+        .collect::<Vec<String>>()
+        .join("\n");
+    format!("Ingredients:\n{ingredients}\n\nSteps:\n{steps}")
+}
+
+/// Escapes TEXT values (RFC 5545 3.3.11)
+fn escape_text(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Folds content lines longer than 75 octets without splitting characters
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut len = 0;
+    for c in line.chars() {
+        if len + c.len_utf8() > ICS_LINE_LIMIT {
+            folded.push_str("\r\n ");
+            len = 1;
+        }
+        folded.push(c);
+        len += c.len_utf8();
+    }
+    folded
+}
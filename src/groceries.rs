@@ -0,0 +1,115 @@
+//! Module for combining the grocery lists of several plans, e.g. to shop for two weeks at once
+//!
+//! Plans are given by date, or selected by the range their first day falls in. Amounts of the
+//! same ingredient are summed across plans, and each line lists the plans needing it
+use crate::category::Categories;
+use crate::config::Household;
+use crate::errors::{PlanError, RecipeParsingError};
+use crate::pantry::Pantry;
+use crate::plan::{grocery_rows, load_plans, Plan};
+use crate::utils::{json_output, print_json};
+use crate::{ingredient_key, CombinedGroceryRow, Ingredient};
+use chrono::NaiveDate;
+use colored::*;
+use std::collections::{BTreeSet, HashMap};
+use tabled::{object::Columns, Format, Modify, Style, Table};
+
+/// Logic for printing one grocery list for the plans on `dates`, or else for every plan
+/// starting between `from` and `to`
+pub fn combined_groceries(
+    recipe_dir: &str,
+    plan_dir: &str,
+    dates: &[NaiveDate],
+    from: &Option<NaiveDate>,
+    to: &Option<NaiveDate>,
+    household: &Household,
+) -> Result<(), PlanError> {
+    let mut plans = select_plans(recipe_dir, plan_dir, dates, from, to)?;
+    if plans.is_empty() {
+        return Err(PlanError::NoPlans);
+    }
+    let groceries = combine_groceries(&mut plans)?;
+    let pantry = Pantry::load(&household.pantry_file)?;
+    let categories = Categories::load(&household.category_file)?;
+    let items = groceries
+        .iter()
+        .map(|(ingr, _)| ingr.clone())
+        .collect::<Vec<Ingredient>>();
+    let sources = groceries
+        .iter()
+        .map(|(ingr, dates)| (ingr.key(), dates))
+        .collect::<HashMap<String, &BTreeSet<NaiveDate>>>();
+    let rows = grocery_rows(&items, &pantry, &categories)
+        .into_iter()
+        .map(|row| {
+            let plans = sources
+                .get(&ingredient_key(&row.Ingredient, &row.Unit))
+                .iter()
+                .flat_map(|x| x.iter())
+                .map(|x| x.to_string())
+                .collect::<Vec<String>>();
+            CombinedGroceryRow {
+                Grocery: row,
+                Plans: plans.join(", "),
+            }
+        })
+        .collect::<Vec<CombinedGroceryRow>>();
+    if json_output() {
+        print_json(&rows)?;
+        return Ok(());
+    }
+    let table = Table::new(rows)
+        .with(Style::psql())
+        .with(Modify::new(Columns::single(4)).with(Format::new(|s| s.red().to_string())));
+    println!("{table}");
+    Ok(())
+}
+
+/// Plans on `dates`, or if none are given every plan starting between `from` and `to`,
+/// in order of date
+pub fn select_plans(
+    recipe_dir: &str,
+    plan_dir: &str,
+    dates: &[NaiveDate],
+    from: &Option<NaiveDate>,
+    to: &Option<NaiveDate>,
+) -> Result<Vec<Plan>, RecipeParsingError> {
+    let mut dates = match dates.is_empty() {
+        true => load_plans(plan_dir)?
+            .iter()
+            .map(|x| x.date)
+            .filter(|x| from.is_none_or(|from| *x >= from) && to.is_none_or(|to| *x <= to))
+            .collect::<Vec<NaiveDate>>(),
+        false => dates.to_vec(),
+    };
+    dates.sort();
+    dates.dedup();
+    dates
+        .iter()
+        .map(|x| Plan::load(x, recipe_dir, plan_dir))
+        .collect()
+}
+
+/// Sums the groceries of plans, with the dates of the plans needing each
+pub fn combine_groceries(
+    plans: &mut [Plan],
+) -> Result<Vec<(Ingredient, BTreeSet<NaiveDate>)>, RecipeParsingError> {
+    let mut combined: HashMap<String, (Ingredient, BTreeSet<NaiveDate>)> = HashMap::new();
+    for plan in plans.iter_mut() {
+        let date = plan.date;
+        for ingr in plan.compile_groceries()?.groceries() {
+            let (total, dates) = combined.entry(ingr.key()).or_insert_with(|| {
+                (
+                    Ingredient {
+                        amount: 0.0,
+                        ..ingr.clone()
+                    },
+                    BTreeSet::new(),
+                )
+            });
+            total.amount += ingr.amount;
+            dates.insert(date);
+        }
+    }
+    Ok(combined.into_values().collect())
+}
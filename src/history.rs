@@ -0,0 +1,141 @@
+//! Module for reporting how often recipes are cooked, read from every plan on disk
+//!
+//! Only cooks are counted, leftovers of a cook are not. Recipes last planned at least
+//! `FORGOTTEN_AFTER` days ago (or `history --forgotten-after`) are reported as forgotten,
+//! most planned first, and the planner suggests them while picking recipes
+use crate::errors::RecipeParsingError;
+use crate::plan::{load_plans, Plan};
+use crate::utils::{json_output, print_json, print_table, title};
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use tabled::Tabled;
+
+/// Days since a recipe was last planned before it counts as forgotten
+pub const FORGOTTEN_AFTER: u32 = 42;
+
+/// Number of forgotten recipes suggested by the planner
+pub const SUGGESTIONS: usize = 3;
+
+/// Cooking record of a single recipe across plans
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RecipeHistory {
+    /// Recipe slug
+    pub recipe: String,
+    /// Number of times the recipe was planned
+    pub planned: usize,
+    /// Latest day the recipe was planned
+    pub last: NaiveDate,
+    /// Mean number of days between the days it was planned, unless planned on a single day
+    pub average_gap: Option<f32>,
+}
+
+/// Struct for listing the history of a recipe
+#[allow(non_snake_case)]
+#[derive(Tabled)]
+struct HistoryRow {
+    Recipe: String,
+    Planned: usize,
+    #[tabled(rename = "Last Planned")]
+    LastPlanned: NaiveDate,
+    #[tabled(rename = "Average Gap (days)")]
+    AverageGap: String,
+}
+
+/// Struct for listing forgotten recipes
+#[allow(non_snake_case)]
+#[derive(Tabled)]
+struct ForgottenRow {
+    Recipe: String,
+    Planned: usize,
+    #[tabled(rename = "Days Since")]
+    DaysSince: i64,
+}
+
+/// History as printed with `--json`
+#[derive(Serialize)]
+struct HistoryReport<'a> {
+    recipes: &'a [RecipeHistory],
+    forgotten: Vec<&'a RecipeHistory>,
+}
+
+/// Logic for printing the history of every planned recipe, then the forgotten ones
+pub fn show_history(
+    plan_dir: &str,
+    today: &NaiveDate,
+    forgotten_after: &u32,
+) -> Result<(), RecipeParsingError> {
+    let history = recipe_history(&load_plans(plan_dir)?);
+    let forgotten = forgotten(&history, *today, *forgotten_after);
+    if json_output() {
+        print_json(&HistoryReport {
+            recipes: &history,
+            forgotten,
+        })?;
+        return Ok(());
+    }
+    title("\t\u{21F8} History\n\n");
+    let rows = history
+        .iter()
+        .map(|x| HistoryRow {
+            Recipe: x.recipe.clone(),
+            Planned: x.planned,
+            LastPlanned: x.last,
+            AverageGap: x.average_gap.map(|x| format!("{x:.1}")).unwrap_or_default(),
+        })
+        .collect::<Vec<HistoryRow>>();
+    print_table(&rows);
+    println!("\nNot planned in {forgotten_after} days or more");
+    let rows = forgotten
+        .iter()
+        .map(|x| ForgottenRow {
+            Recipe: x.recipe.clone(),
+            Planned: x.planned,
+            DaysSince: (*today - x.last).num_days(),
+        })
+        .collect::<Vec<ForgottenRow>>();
+    print_table(&rows);
+    Ok(())
+}
+
+/// History of every recipe cooked in `plans`, most planned first
+pub fn recipe_history(plans: &[Plan]) -> Vec<RecipeHistory> {
+    let mut planned: BTreeMap<String, (usize, BTreeSet<NaiveDate>)> = BTreeMap::new();
+    for (day, _, meal) in plans.iter().flat_map(|x| x.meals()) {
+        if meal.is_leftovers() {
+            continue;
+        }
+        let (count, days) = planned.entry(meal.recipe().clone()).or_default();
+        *count += 1;
+        days.insert(day);
+    }
+    let mut history = planned
+        .into_iter()
+        .filter_map(|(recipe, (planned, days))| {
+            let (first, last) = (*days.iter().next()?, *days.iter().next_back()?);
+            let average_gap = match days.len() {
+                1 => None,
+                n => Some((last - first).num_days() as f32 / (n - 1) as f32),
+            };
+            Some(RecipeHistory {
+                recipe,
+                planned,
+                last,
+                average_gap,
+            })
+        })
+        .collect::<Vec<RecipeHistory>>();
+    history.sort_by_key(|x| (Reverse(x.planned), x.recipe.clone()));
+    history
+}
+
+/// Recipes last planned at least `after` days before `today`, most planned first
+pub fn forgotten(history: &[RecipeHistory], today: NaiveDate, after: u32) -> Vec<&RecipeHistory> {
+    let mut forgotten = history
+        .iter()
+        .filter(|x| (today - x.last).num_days() >= after as i64)
+        .collect::<Vec<&RecipeHistory>>();
+    forgotten.sort_by_key(|x| (Reverse(x.planned), x.last));
+    forgotten
+}
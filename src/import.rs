@@ -0,0 +1,210 @@
+//! Module for importing recipes from other formats
+use crate::errors::{ImportError, IngredientParsingError};
+use crate::orf::OrfRecipe;
+use crate::utils::{
+    check_name, get_recipe_out_path, json_output, notify, notify_saved, print_json, read_input,
+    read_yaml,
+};
+use crate::{parse_amount, Ingredient, Recipe, Unit};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+
+/// One row of a recipe spreadsheet, each row holds at most one ingredient and one step
+#[derive(Debug, Deserialize)]
+struct CsvRow {
+    name: String,
+    #[serde(default)]
+    tags: String,
+    #[serde(default)]
+    amount: String,
+    #[serde(default)]
+    unit: String,
+    #[serde(default)]
+    ingredient: String,
+    #[serde(default)]
+    step: String,
+}
+
+/// Error found on a specific line of an import file
+#[derive(Debug, Serialize)]
+pub struct RowError {
+    pub line: u64,
+    pub recipe: String,
+    pub error: String,
+}
+
+/// Summary of an import, listing written recipe paths and row-level errors
+#[derive(Debug, Default, Serialize)]
+pub struct ImportReport {
+    pub imported: Vec<String>,
+    pub errors: Vec<RowError>,
+}
+
+/// Logic for importing recipes from a CSV spreadsheet.
+/// Recipes with invalid rows or an existing recipe file are skipped and reported
+pub fn import_csv(recipe_dir: &str, path: &str) -> Result<(), ImportError> {
+    let (recipes, mut errors) = parse_csv(read_input(Path::new(path))?.as_bytes());
+    let mut report = ImportReport::default();
+    for (line, recipe) in recipes {
+        if errors.iter().any(|e| e.recipe == recipe.name) {
+            continue;
+        }
+        if get_recipe_out_path(recipe_dir, &recipe.name).exists() {
+            errors.push(RowError {
+                line,
+                recipe: recipe.name.clone(),
+                error: ImportError::AlreadyExists(recipe.name).to_string(),
+            });
+            continue;
+        }
+        let outpath = recipe.write(recipe_dir)?;
+        report.imported.push(outpath.to_string_lossy().to_string());
+    }
+    errors.sort_by_key(|e| e.line);
+    report.errors = errors;
+
+    if json_output() {
+        print_json(&report)?;
+        return Ok(());
+    }
+    report
+        .imported
+        .iter()
+        .for_each(|x| notify(&format!("Recipe saved to {x}")));
+    report.errors.iter().for_each(|e| {
+        let recipe = match e.recipe.is_empty() {
+            true => String::new(),
+            false => format!(" ({})", e.recipe),
+        };
+        notify(&format!(
+            "{}{recipe}: {}",
+            format!("line {}", e.line).red(),
+            e.error
+        ))
+    });
+    notify(&format!(
+        "Imported {} recipes with {} errors",
+        report.imported.len(),
+        report.errors.len()
+    ));
+    Ok(())
+}
+
+/// Logic for importing a recipe from an Open Recipe Format file
+pub fn import_orf(recipe_dir: &str, path: &str) -> Result<(), ImportError> {
+    let orf: OrfRecipe = read_yaml(Path::new(path))?;
+    let recipe = Recipe::try_from(orf)?;
+    check_name(&recipe.name)?;
+    if get_recipe_out_path(recipe_dir, &recipe.name).exists() {
+        return Err(ImportError::AlreadyExists(recipe.name));
+    }
+    let outpath = recipe.write(recipe_dir)?;
+    notify_saved("Recipe", &outpath);
+    if json_output() {
+        print_json(&recipe)?;
+    }
+    Ok(())
+}
+
+/// Groups spreadsheet rows into recipes, in order of first appearance.
+/// Returns each recipe with the line it starts on, along with any row-level errors.
+///
+/// Expects a header row with the columns `name, tags, amount, unit, ingredient, step`.
+/// Tags may be separated by `,` or `;` and are merged across the rows of a recipe.
+pub fn parse_csv<R: Read>(reader: R) -> (Vec<(u64, Recipe)>, Vec<RowError>) {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+    let mut recipes: Vec<(u64, Recipe)> = vec![];
+    let mut errors: Vec<RowError> = vec![];
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(e) => {
+            errors.push(row_error(1, "", e.into()));
+            return (recipes, errors);
+        }
+    };
+    for record in reader.records() {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                let line = e.position().map_or(0, |x| x.line());
+                errors.push(row_error(line, "", e.into()));
+                continue;
+            }
+        };
+        let line = record.position().map_or(0, |x| x.line());
+        let row = match record.deserialize::<CsvRow>(Some(&headers)) {
+            Ok(row) => row,
+            Err(e) => {
+                errors.push(row_error(line, "", e.into()));
+                continue;
+            }
+        };
+        if row.name.is_empty() {
+            errors.push(row_error(line, "", ImportError::NoName));
+            continue;
+        }
+        if let Err(e) = check_name(&row.name) {
+            errors.push(row_error(line, &row.name, e.into()));
+            continue;
+        }
+        let idx = match recipes.iter().position(|(_, x)| x.name == row.name) {
+            Some(idx) => idx,
+            None => {
+                let recipe = Recipe {
+                    name: row.name.clone(),
+                    tags: vec![],
+                    servings: None,
+                    ingredients: vec![],
+                    steps: vec![],
+                };
+                recipes.push((line, recipe));
+                recipes.len() - 1
+            }
+        };
+        if let Err(e) = add_row(&mut recipes[idx].1, &row) {
+            errors.push(row_error(line, &row.name, e));
+        }
+    }
+    (recipes, errors)
+}
+
+/// Adds the tags, ingredient, and step of a single row to a recipe
+fn add_row(recipe: &mut Recipe, row: &CsvRow) -> Result<(), ImportError> {
+    if !(row.amount.is_empty() && row.unit.is_empty() && row.ingredient.is_empty()) {
+        if row.ingredient.is_empty() {
+            return Err(IngredientParsingError::NoIngredient.into());
+        }
+        recipe.ingredients.push(Ingredient {
+            name: row.ingredient.clone(),
+            amount: parse_amount(&row.amount)?,
+            unit: Unit::from_str(&row.unit)?,
+        });
+    }
+    row.tags
+        .split([',', ';'])
+        .map(|x| x.trim())
+        .filter(|x| !x.is_empty())
+        .for_each(|tag| {
+            if !recipe.tags.iter().any(|x| x == tag) {
+                recipe.tags.push(tag.into())
+            }
+        });
+    if !row.step.is_empty() {
+        recipe.steps.push(row.step.clone());
+    }
+    Ok(())
+}
+
+/// Builds a row-level error report entry
+fn row_error(line: u64, recipe: &str, error: ImportError) -> RowError {
+    RowError {
+        line,
+        recipe: recipe.into(),
+        error: error.to_string(),
+    }
+}
@@ -3,11 +3,22 @@
 //! meals for the week straight foward.  
 //!
 //! # Subcommands
-//! The tool is split into 4 separate subcommands
-//! - `add`     - Interactively define and save a recipe
+//! The tool is split into 14 separate subcommands
+//! - `add`     - Define and save a recipe, interactively or from flags / a file
 //! - `view`    - Search through recipes/tags via `FuzzySearch`
-//! - `plan`    - Plan a meal for the week and generate a grocery list
+//! - `list`    - List recipes filtered by tags / ingredients and sorted
+//! - `show`    - Show a single recipe by name, optionally scaled
+//! - `plan`    - Plan meals for the week, or any number of days, and generate a grocery list.
+//!   `plan edit` moves, removes, or replaces meals of an existing plan
+//! - `history` - Report how often and how recently recipes were planned, see [`history`]
+//! - `pantry`  - Track ingredients on hand, offsetting grocery lists, see [`pantry`]
+//! - `template` - Save plans as reusable templates, see [`template`]
+//! - `groceries` - Combine the grocery lists of several plans or a date range
 //! - `behold`  - Display weekly plan or view detailed breakdown by day
+//! - `export`  - Export plans to iCalendar (`.ics`) or recipes to Open Recipe Format
+//! - `import`  - Import recipes from a CSV spreadsheet or Open Recipe Format
+//! - `print`   - Print a week-at-a-glance sheet with plan, groceries, and notes
+//! - `completions` - Print a bash, zsh, or fish completion script
 //!
 //! # Installing Averse
 //! `Averse` is compiled to an executable binary using `cargo`.
@@ -18,35 +29,98 @@
 //! one that contain `recipes` and one that contains `plans`. See the
 //! repository directories for examples.
 //!
+//! Placing an `averse.toml` in that directory lets Averse run from any subdirectory,
+//! see [`config`] for the available settings.
+//!
+//! The same directory may hold a `pantry.yaml` of ingredients on hand, see [`pantry`], and a
+//! `categories.yaml` setting grocery categories and store aisle order, see [`category`].
+//! Either may be moved with `pantry-file` and `category-file`.
+//!
 //! An example workflow for using Averse:
 //!
 //! 1. Recipes are interactively added via `averse add`
 //! 2. Recipes are browsed via `averse view`
-//! 3. Existing recipes are planned for the week using `averse plan`
+//! 3. Existing recipes are planned for the week using `averse plan`, or `averse plan --auto`
 //! 4. Meals for a week can be viewed using `averse behold`
+//! 5. Plans can be added to a calendar using `averse export ics`
+//!
+//!
+//! Every subcommand accepts a global `--json` flag, which suppresses screen clearing and
+//! color, and prints machine-readable JSON to stdout. Files written by `print` and `export`
+//! are reported as `{"path": ...}`. Subcommands that can only prompt, e.g. `add` without
+//! `--name` or `--from-file`, fail with an input error naming the flags to use instead.
 //!
+//! Errors are printed to stderr and exit with a code per [`errors::ErrorCategory`]:
+//! 2 for invalid usage, 3 for invalid input, 4 when a recipe / plan / file is missing,
+//! 5 when a file can't be parsed, 6 for other I/O errors, 7 for an invalid `averse.toml`,
+//! and 130 when a prompt is cancelled with Esc or Ctrl-C.
 //!
 //! # Example Commands
-//! ```
+//! ```text
 //! averse add --recipe-dir /path/to/recipes --plan-dir /path/to/plans
+//! averse add --name tacos --tag mexican --ingredient "1 lb beef" --step "Brown beef"
+//! averse add --from-file tacos.yaml
 //! averse view
+//! averse show chili --servings 8
+//! averse list --tag dinner --without-tag italian --sort last-planned
 //! averse plan --date 2022-07-31
+//! averse plan --date 2022-08-07 --meal Monday=chili --meal Tuesday=chicken-teriyaki
+//! averse plan --date next-sunday --meal sun=chili --meal sun:lunch=dill-pasta-salad
+//! averse plan --date 2022-08-11 --days 4
+//! averse plan --meal sun=chili --leftovers mon=chili --leftovers tue:lunch=chili
+//! averse plan --auto --slot breakfast --slot dinner --require breakfast=tag:breakfast --avoid-recent 2
+//! averse plan --date 2022-08-14 --from-file skeleton.yaml
+//! averse plan --date next-sunday --template weeknight-rotation
+//! averse plan --date 2022-08-07 --copy-from 2022-07-24
+//! averse plan edit 2022-07-31
+//! averse plan cooked 2022-07-31
+//! averse pantry add "2 can kidney beans" "1 lb beef"
+//! averse template save weeknight-rotation 2022-07-31
+//! averse history --forgotten-after 60
+//! averse groceries --from 2022-07-24 --to 2022-08-07
+//! averse groceries 2022-07-24 2022-07-31
 //! averse behold
+//! averse --json behold
+//! averse export ics 2022-07-31
+//! averse export orf chili --output chili.yml
+//! averse import csv recipes.csv
+//! averse import orf chili.yml
+//! averse print 2022-07-31 --html --output week.html
+//! source <(averse completions bash)
 //! ```
 //!
 
 pub mod add;
+pub mod auto;
 pub mod behold;
+pub mod category;
+pub mod completions;
+pub mod config;
+pub mod date;
 pub mod errors;
+pub mod export;
+pub mod groceries;
+pub mod history;
+pub mod import;
+pub mod list;
+pub mod orf;
+pub mod pantry;
 pub mod plan;
+pub mod print;
+pub mod query;
+pub mod show;
+pub mod template;
 pub mod utils;
 pub mod view;
 
+use crate::category::Category;
 use crate::errors::{IngredientParsingError, RecipeParsingError};
+use crate::plan::{Meals, Slot};
+use crate::utils::{check_name, get_recipe_out_path, read_yaml, write_file};
+use chrono::NaiveDate;
 use colored::*;
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter, Result as FmtResult};
-use std::fs;
 use std::path::PathBuf;
 use std::str::FromStr;
 use tabled::Tabled;
@@ -60,7 +134,7 @@ const UNITS: [&str; 10] = [
 pub const WEEK: [&str; 7] = [
     "Sunday",
     "Monday",
-    "Tuedsay",
+    "Tuesday",
     "Wednesday",
     "Thursday",
     "Friday",
@@ -72,8 +146,11 @@ pub const WEEK: [&str; 7] = [
 pub struct Recipe {
     /// Name of the recipe,
     name: String,
-    ///
+    /// Tags used for searching / filtering
     tags: Vec<String>,
+    /// Number of servings the recipe makes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    servings: Option<u16>,
     ingredients: Vec<Ingredient>,
     steps: Vec<String>,
 }
@@ -83,10 +160,16 @@ impl Recipe {
     pub fn to_row(self, id: usize) -> RecipeRow {
         RecipeRow {
             ID: id,
+            Ingredients: self.ingredients.len(),
             Name: self.name,
             Tags: self.tags.join(", "),
+            LastPlanned: String::new(),
         }
     }
+    /// File name form of the recipe name, used to reference recipes in plans
+    pub fn slug(&self) -> String {
+        self.name.replace(' ', "-")
+    }
     /// Provide summary details of a recipe
     pub fn summary(&self) -> String {
         format!(
@@ -95,6 +178,25 @@ impl Recipe {
             self.tags.join(", ")
         )
     }
+    /// Returns a copy of the recipe with ingredient amounts (and servings) multiplied by `factor`, rounded to hundredths
+    pub fn scale(&self, factor: f32) -> Recipe {
+        let mut recipe = self.clone();
+        recipe
+            .ingredients
+            .iter_mut()
+            .for_each(|x| x.amount = (x.amount * factor * 100.0).round() / 100.0);
+        recipe.servings = self
+            .servings
+            .map(|x| (x as f32 * factor).round().max(1.0) as u16);
+        recipe
+    }
+    /// Serializes the recipe to `recipe_dir`, returning the output path
+    pub fn write(&self, recipe_dir: &str) -> Result<PathBuf, RecipeParsingError> {
+        check_name(&self.name)?;
+        let recipe_path = get_recipe_out_path(recipe_dir, &self.name);
+        write_file(&recipe_path, &serde_yaml::to_string(&self)?)?;
+        Ok(recipe_path)
+    }
 }
 
 impl Display for Recipe {
@@ -102,6 +204,10 @@ impl Display for Recipe {
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
         let name = self.name.purple();
         let tags = self.tags.join(", ").green();
+        let servings = match self.servings {
+            Some(n) => format!("\n\t{} {}", "→ Servings:".green(), n.to_string().green()),
+            None => String::new(),
+        };
         let ingredients = self
             .ingredients
             .iter()
@@ -118,7 +224,7 @@ impl Display for Recipe {
             .white();
         write!(
             f,
-            "{name}\n\t{} {tags}\n\n{} {ingredients}\n\n🡢 {steps}\n",
+            "{name}\n\t{} {tags}{servings}\n\n{} {ingredients}\n\n🡢 {steps}\n",
             "→ Tags:".green(),
             "⇒".blue()
         )
@@ -129,10 +235,7 @@ impl TryFrom<&PathBuf> for Recipe {
     type Error = RecipeParsingError;
     /// For deserializing a recipe from a path
     fn try_from(path: &PathBuf) -> Result<Self, Self::Error> {
-        if !path.exists() {
-            panic!("The file does not exist dumbass: {path:?}")
-        }
-        Ok(serde_yaml::from_str(&fs::read_to_string(&path)?)?)
+        read_yaml(path)
     }
 }
 
@@ -144,19 +247,47 @@ pub struct Ingredient {
     unit: Unit,
 }
 
+impl Ingredient {
+    /// Key matching the same ingredient across recipes, plans, and the pantry,
+    /// see `ingredient_key`
+    pub fn key(&self) -> String {
+        ingredient_key(&self.name, &self.unit)
+    }
+}
+
+/// Key matching an ingredient by name (case-insensitive) and unit
+pub fn ingredient_key(name: &str, unit: &Unit) -> String {
+    format!("{}_{unit}", name.trim().to_lowercase())
+}
+
 impl Display for Ingredient {
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
         write!(f, "{} {} {}", self.amount, self.unit, self.name)
     }
 }
 
+/// Parses an ingredient amount, which must be a finite number of zero or more
+pub fn parse_amount(input: &str) -> Result<f32, IngredientParsingError> {
+    let amount = input.parse::<f32>()?;
+    match amount.is_finite() && amount >= 0.0 {
+        true => Ok(amount),
+        false => Err(IngredientParsingError::OutOfRange(amount)),
+    }
+}
+
 impl FromStr for Ingredient {
     type Err = IngredientParsingError;
     fn from_str(input: &str) -> Result<Ingredient, Self::Err> {
-        let split = input.split(" ").collect::<Vec<_>>();
-        let amount = split[0].parse::<f32>()?;
-        let unit = split[1].parse::<Unit>()?;
-        let name = split[2..].join(" ");
+        let mut split = input.split_whitespace();
+        let amount = parse_amount(split.next().ok_or(IngredientParsingError::NoIngredient)?)?;
+        let unit = split
+            .next()
+            .ok_or(IngredientParsingError::NoIngredient)?
+            .parse::<Unit>()?;
+        let name = split.collect::<Vec<_>>().join(" ");
+        if name.is_empty() {
+            return Err(IngredientParsingError::NoIngredient);
+        }
         Ok(Ingredient { name, amount, unit })
     }
 }
@@ -214,11 +345,14 @@ impl FromStr for Unit {
 
 /// Struct for printing Recipe summary information
 #[allow(non_snake_case)]
-#[derive(Tabled)]
+#[derive(Tabled, Serialize)]
 pub struct RecipeRow {
     ID: usize,
     pub Name: String,
     Tags: String,
+    Ingredients: usize,
+    #[tabled(rename = "Last Planned")]
+    pub LastPlanned: String,
 }
 
 /// Struct for listing ingredients
@@ -249,26 +383,72 @@ pub struct StepRow {
     pub Details: String,
 }
 
-/// Struct for displaying meal plans for the week
-#[allow(non_snake_case)]
-#[derive(Tabled)]
+/// Struct for displaying one week of a meal plan, see `plan::plan_table`
+#[derive(Debug, Clone)]
 pub struct PlanRow {
-    Date: String,
-    Sunday: String,
-    Monday: String,
-    Tuesday: String,
-    Wednesday: String,
-    Thursday: String,
-    Friday: String,
-    Saturday: String,
+    /// Date of the plan, only set on the plan's first week
+    pub plan: Option<NaiveDate>,
+    /// Seven consecutive days with their meals, `None` for days outside the plan
+    pub days: Vec<(NaiveDate, Option<Meals>)>,
+}
+
+impl PlanRow {
+    /// Table rows for the week, a row of dates followed by a row per meal slot
+    pub fn rows(&self, slots: &[Slot]) -> Vec<Vec<String>> {
+        let mut dates = vec![
+            self.plan.map(|x| x.to_string()).unwrap_or_default(),
+            String::new(),
+        ];
+        dates.extend(self.days.iter().map(|(day, meals)| match meals {
+            Some(_) => day.format("%m-%d").to_string(),
+            None => String::new(),
+        }));
+        let mut rows = vec![dates];
+        for slot in slots {
+            let mut row = vec![String::new(), slot.to_string()];
+            row.extend(self.days.iter().map(|(_, meals)| {
+                meals
+                    .as_ref()
+                    .and_then(|x| x.get(slot))
+                    .map(|meals| {
+                        meals
+                            .iter()
+                            .map(|x| x.to_string())
+                            .collect::<Vec<String>>()
+                            .join("\n")
+                    })
+                    .unwrap_or_default()
+            }));
+            rows.push(row);
+        }
+        rows
+    }
 }
 
 /// Struct for listing groceries for the week
 #[allow(non_snake_case)]
-#[derive(Debug, Tabled)]
+#[derive(Debug, Tabled, Serialize)]
 pub struct GroceryRow {
-    Id: usize,
-    Amount: f32,
-    Unit: Unit,
-    Ingredient: String,
+    pub Id: usize,
+    pub Category: Category,
+    /// Amount needed by the plan, shown as Need next to Have and Buy
+    #[tabled(rename = "Need")]
+    pub Amount: f32,
+    /// Amount on hand in the pantry
+    pub Have: f32,
+    /// Amount left to buy
+    pub Buy: f32,
+    pub Unit: Unit,
+    pub Ingredient: String,
+}
+
+/// Struct for listing groceries combined from several plans
+#[allow(non_snake_case)]
+#[derive(Debug, Tabled, Serialize)]
+pub struct CombinedGroceryRow {
+    #[tabled(inline)]
+    #[serde(flatten)]
+    pub Grocery: GroceryRow,
+    /// Dates of the plans needing the grocery
+    pub Plans: String,
 }
@@ -0,0 +1,84 @@
+//! Module for listing recipes with filters and sorting
+use crate::errors::RecipeParsingError;
+use crate::plan::{last_planned, load_plans};
+use crate::query::{Query, Term};
+use crate::utils::{json_output, print_json, print_table, search_recipes};
+use crate::RecipeRow;
+use clap::ArgEnum;
+use console::Term as Terminal;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::Path;
+use tabled::Tabled;
+
+/// Orderings for listed recipes
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortBy {
+    Name,
+    Tag,
+    Ingredients,
+    LastPlanned,
+}
+
+/// Logic for listing recipes matching `query`, see `query` module for the syntax
+pub fn list_recipes(
+    recipe_dir: &str,
+    plan_dir: &str,
+    query: &Query,
+    sort: &SortBy,
+) -> Result<(), RecipeParsingError> {
+    // No plan dir yet means nothing has been planned
+    let last = match Path::new(plan_dir).is_dir() {
+        true => last_planned(&load_plans(plan_dir)?),
+        false => HashMap::new(),
+    };
+    let mut recipes = search_recipes(recipe_dir, query)?;
+    match sort {
+        SortBy::Name => recipes.sort_by_key(|x| x.name.to_lowercase()),
+        SortBy::Tag => recipes.sort_by_key(|x| (x.tags.first().cloned(), x.name.to_lowercase())),
+        SortBy::Ingredients => recipes.sort_by_key(|x| (x.ingredients.len(), x.name.clone())),
+        SortBy::LastPlanned => {
+            recipes.sort_by_key(|x| (Reverse(last.get(&x.slug()).cloned()), x.name.clone()))
+        }
+    }
+    let rows = recipes
+        .into_iter()
+        .enumerate()
+        .map(|(i, x)| {
+            let planned = last
+                .get(&x.slug())
+                .map(|x| x.to_string())
+                .unwrap_or_default();
+            RecipeRow {
+                LastPlanned: planned,
+                ..x.to_row(i)
+            }
+        })
+        .collect::<Vec<RecipeRow>>();
+
+    if json_output() {
+        print_json(&rows)?;
+    } else if Terminal::stdout().is_term() {
+        print_table(&rows);
+    } else {
+        // Plain tab-separated text when piped
+        println!("{}", RecipeRow::headers().join("\t"));
+        rows.iter()
+            .for_each(|x| println!("{}", x.fields().join("\t")));
+    }
+    Ok(())
+}
+
+/// Query equivalent of the `list` filter flags: recipes with every tag in `tags`,
+/// none of `without_tags`, and every ingredient in `ingredients`
+pub fn filter_query(tags: &[String], without_tags: &[String], ingredients: &[String]) -> Query {
+    let tag = |x: &String| Query::Term(Term::Tag(x.to_lowercase()));
+    let ingredient = |x: &String| Query::Term(Term::Ingredient(x.to_lowercase()));
+    Query::And(
+        tags.iter()
+            .map(tag)
+            .chain(without_tags.iter().map(|x| Query::Not(Box::new(tag(x)))))
+            .chain(ingredients.iter().map(ingredient))
+            .collect(),
+    )
+}
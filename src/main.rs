@@ -1,17 +1,41 @@
-use averse::{add, behold, plan, view};
-use clap::{Parser, Subcommand};
+use averse::config::Config;
+use averse::date::{parse_date_arg, today, upcoming_week};
+use averse::errors::{AverseError, ErrorCategory};
+use averse::list::{filter_query, SortBy};
+use averse::plan::Slot;
+use averse::query::Query;
+use averse::template::Template;
+use averse::utils::set_json_output;
+use averse::{
+    add, behold, completions, export, groceries, history, import, list, pantry, plan, print, show,
+    template, view,
+};
+use chrono::NaiveDate;
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use colored::*;
+use console::Term as Terminal;
+use std::process;
 
 /// CLI
 #[derive(Parser)]
 #[clap(author, version, about)]
 struct Cli {
-    /// Path to JSON-encoded recipe directory
-    #[clap(short, long, default_value_t=String::from("./recipes"))]
-    recipe_dir: String,
+    /// Path to JSON-encoded recipe directory [default: ./recipes or set in averse.toml]
+    #[clap(short, long)]
+    recipe_dir: Option<String>,
 
-    /// Path to plans directory
-    #[clap(short, long, default_value_t=String::from("./plans"))]
-    plan_dir: String,
+    /// Path to plans directory [default: ./plans or set in averse.toml]
+    #[clap(short, long)]
+    plan_dir: Option<String>,
+
+    /// Path to plan templates directory [default: ./templates or set in averse.toml]
+    #[clap(long)]
+    template_dir: Option<String>,
+
+    /// Print machine-readable JSON instead of tables, without colors or screen clearing
+    #[clap(long, global = true)]
+    json: bool,
 
     #[clap(subcommand)]
     command: Commands,
@@ -19,36 +43,495 @@ struct Cli {
 
 #[derive(Subcommand)]
 enum Commands {
-    /// Add recipe interactively
-    Add,
+    /// Add recipe interactively, or from flags / a file without prompting
+    Add {
+        /// Recipe name, skips the interactive prompts
+        #[clap(short, long, conflicts_with = "from-file")]
+        name: Option<String>,
+        /// Tag for the recipe, may be repeated
+        #[clap(short, long, requires = "name")]
+        tag: Vec<String>,
+        /// Number of servings the recipe makes
+        #[clap(long, requires = "name")]
+        servings: Option<u16>,
+        /// Ingredient in the form <AMOUNT> <UNIT> <INGREDIENT> e.g. "1 lb beef", may be repeated
+        #[clap(short, long, requires = "name")]
+        ingredient: Vec<String>,
+        /// Recipe step, may be repeated
+        #[clap(short, long, requires = "name")]
+        step: Vec<String>,
+        /// Path to a YAML recipe file, or - to read from stdin
+        #[clap(short, long)]
+        from_file: Option<String>,
+        /// Overwrite an existing recipe with the same name
+        #[clap(long)]
+        force: bool,
+    },
     /// View & filter recipes
-    View,
-    /// Plan meals + grocery list for the week
+    View {
+        /// Only show recipes matching a query e.g. "tag:soup -ingredient:beef"
+        #[clap(allow_hyphen_values = true, parse(try_from_str), default_value = "")]
+        query: Query,
+    },
+    /// Show a single recipe by name, slug, or unique prefix
+    Show {
+        /// Recipe to show e.g. chili
+        recipe: String,
+        /// Only show ingredients
+        #[clap(short, long, conflicts_with = "steps")]
+        ingredients: bool,
+        /// Only show steps
+        #[clap(short, long)]
+        steps: bool,
+        /// Scale ingredients to this many servings
+        #[clap(long, conflicts_with = "scale")]
+        servings: Option<u16>,
+        /// Multiply ingredient amounts by this factor
+        #[clap(long)]
+        scale: Option<f32>,
+    },
+    /// List recipes in a table, filtered and sorted
+    List {
+        /// Only list recipes matching a query e.g. "tag:soup -ingredient:beef"
+        #[clap(allow_hyphen_values = true, parse(try_from_str), default_value = "")]
+        query: Query,
+        /// Only list recipes with this tag, may be repeated
+        #[clap(short, long)]
+        tag: Vec<String>,
+        /// Exclude recipes with this tag, may be repeated
+        #[clap(short, long)]
+        without_tag: Vec<String>,
+        /// Only list recipes containing this ingredient, may be repeated
+        #[clap(short, long)]
+        ingredient: Vec<String>,
+        /// Order of listed recipes
+        #[clap(short, long, arg_enum, default_value = "name")]
+        sort: SortBy,
+    },
+    /// Plan meals + grocery list for the week, or any number of days
+    #[clap(args_conflicts_with_subcommands = true)]
     Plan {
-        /// Date in the form (YEAR-MONTH-DAY) e.g. 2022-05-15
+        #[clap(subcommand)]
+        action: Option<PlanAction>,
+        /// First day of the plan e.g. 2022-05-15, next-sunday, or +1w [default: the upcoming week]
+        #[clap(short, long, parse(try_from_str = parse_date_arg))]
+        date: Option<NaiveDate>,
+        /// Number of days the plan covers e.g. 4 for a long weekend [default: 7]
+        #[clap(long)]
+        days: Option<u16>,
+        /// Meal in the form DAY[:SLOT]=RECIPE e.g. Monday=chili or mon:breakfast=bagel,
+        /// may be repeated. SLOT is breakfast, lunch, dinner (default), or snack. Skips prompts
         #[clap(short, long)]
-        date: String,
+        meal: Vec<String>,
+        /// Leftovers of an earlier --meal in the form DAY[:SLOT]=RECIPE e.g. tue=chili,
+        /// may be repeated. Leftovers add no groceries
+        #[clap(short, long, requires = "meal")]
+        leftovers: Vec<String>,
+        /// Path to a YAML plan skeleton, or - to read from stdin. Skips prompts
+        #[clap(short, long, conflicts_with = "meal")]
+        from_file: Option<String>,
+        /// Name of a saved template to instantiate, prompting only for its open slots
+        #[clap(short, long, conflicts_with_all = &["meal", "from-file", "auto"])]
+        template: Option<String>,
+        /// Date of an earlier plan to copy to --date e.g. 2022-07-24, keeping the original
+        #[clap(
+            long,
+            parse(try_from_str = parse_date_arg),
+            conflicts_with_all = &["meal", "from-file", "auto", "template"]
+        )]
+        copy_from: Option<NaiveDate>,
+        /// Only pick from recipes matching a query e.g. "tag:dinner -tag:dessert"
+        #[clap(short, long, parse(try_from_str), default_value = "")]
+        query: Query,
+        /// Fill the plan automatically under the [auto] rules in averse.toml and the flags below
+        #[clap(long, conflicts_with_all = &["meal", "from-file"])]
+        auto: bool,
+        /// Meal slot to fill on every day, may be repeated [default: dinner]
+        #[clap(long, requires = "auto", parse(try_from_str))]
+        slot: Vec<Slot>,
+        /// Requirement in the form TARGET=QUERY e.g. fri=tag:pizza or breakfast=tag:breakfast,
+        /// may be repeated
+        #[clap(long, requires = "auto")]
+        require: Vec<String>,
+        /// Number of times a recipe may appear in the plan [default: 1]
+        #[clap(long, requires = "auto")]
+        max_repeats: Option<usize>,
+        /// Skip recipes planned in the latest N plans [default: 0]
+        #[clap(long, requires = "auto")]
+        avoid_recent: Option<usize>,
+        /// Maximum number of distinct groceries in the plan
+        #[clap(long, requires = "auto")]
+        max_groceries: Option<usize>,
+    },
+    /// Report how often and how recently each recipe was planned, and forgotten recipes
+    History {
+        /// Days since a recipe was last planned before it counts as forgotten
+        #[clap(long, default_value_t = history::FORGOTTEN_AFTER)]
+        forgotten_after: u32,
+    },
+    /// Track ingredients on hand in the pantry file set in averse.toml, offsetting groceries
+    Pantry {
+        #[clap(subcommand)]
+        action: PantryAction,
+    },
+    /// Save plans as reusable templates, or list saved templates
+    Template {
+        #[clap(subcommand)]
+        action: TemplateAction,
+    },
+    /// One grocery list for several plans, or for every plan starting in a date range
+    Groceries {
+        /// Dates of the plans to combine e.g. 2022-07-24 2022-07-31
+        #[clap(
+            parse(try_from_str = parse_date_arg),
+            required_unless_present_any = &["from", "to"],
+            conflicts_with_all = &["from", "to"]
+        )]
+        plan: Vec<NaiveDate>,
+        /// Combine plans starting on or after this date e.g. 2022-07-24
+        #[clap(long, parse(try_from_str = parse_date_arg))]
+        from: Option<NaiveDate>,
+        /// Combine plans starting on or before this date e.g. 2022-08-07
+        #[clap(long, parse(try_from_str = parse_date_arg))]
+        to: Option<NaiveDate>,
     },
     /// Display weekly plan, select day to show recipe details
     Behold {
-        /// Number of plans to display
-        #[clap(short, long, default_value_t = 5)]
-        n_plans: usize,
+        /// Date of a single plan to display e.g. 2022-07-31
+        #[clap(parse(try_from_str = parse_date_arg))]
+        plan: Option<NaiveDate>,
+        /// Number of plans to display [default: 5 or set in averse.toml]
+        #[clap(short, long)]
+        n_plans: Option<usize>,
+    },
+    /// Printable week-at-a-glance sheet with groceries and notes
+    Print {
+        /// Date of the plan to print e.g. 2022-07-31
+        #[clap(parse(try_from_str = parse_date_arg))]
+        plan: NaiveDate,
+        /// Render as HTML instead of 80-column plain text
+        #[clap(long)]
+        html: bool,
+        /// Output path, defaults to stdout
+        #[clap(short, long)]
+        output: Option<String>,
+    },
+    /// Export plans or recipes to other formats
+    Export {
+        #[clap(subcommand)]
+        format: ExportFormat,
+    },
+    /// Import recipes from other formats
+    Import {
+        #[clap(subcommand)]
+        format: ImportFormat,
+    },
+    /// Print a shell completion script, e.g. `source <(averse completions bash)`
+    Completions {
+        /// Shell to generate completions for
+        #[clap(arg_enum)]
+        shell: Shell,
+    },
+}
+
+#[derive(Subcommand)]
+enum PlanAction {
+    /// Move, remove, or replace meals of an existing plan, or swap its days
+    Edit {
+        /// Date of the plan to edit e.g. 2022-07-31
+        #[clap(parse(try_from_str = parse_date_arg))]
+        plan: NaiveDate,
+        /// Only replace meals with recipes matching a query e.g. "tag:dinner"
+        #[clap(short, long, parse(try_from_str), default_value = "")]
+        query: Query,
+    },
+    /// Mark a plan cooked, offering to deduct its groceries from the pantry
+    Cooked {
+        /// Date of the cooked plan e.g. 2022-07-31
+        #[clap(parse(try_from_str = parse_date_arg))]
+        plan: NaiveDate,
+    },
+}
+
+#[derive(Subcommand)]
+enum PantryAction {
+    /// Add ingredients in the form <AMOUNT> <UNIT> <INGREDIENT> e.g. "2 can kidney beans"
+    Add {
+        #[clap(required = true)]
+        ingredient: Vec<String>,
+    },
+    /// Remove an amount of ingredients e.g. "1 can kidney beans", or all of one e.g. "kidney beans"
+    Remove {
+        #[clap(required = true)]
+        ingredient: Vec<String>,
+    },
+    /// List the ingredients on hand
+    List,
+}
+
+#[derive(Subcommand)]
+enum TemplateAction {
+    /// Save the meals of a plan as a named template
+    Save {
+        /// Name of the template e.g. weeknight-rotation
+        name: String,
+        /// Date of the plan to save e.g. 2022-07-31
+        #[clap(parse(try_from_str = parse_date_arg))]
+        plan: NaiveDate,
+        /// Overwrite an existing template with the same name
+        #[clap(long)]
+        force: bool,
+    },
+    /// List the names of saved templates
+    List,
+}
+
+#[derive(Subcommand)]
+enum ExportFormat {
+    /// iCalendar file with one event per planned recipe
+    Ics {
+        /// Date of the plan to export e.g. 2022-07-31
+        #[clap(parse(try_from_str = parse_date_arg))]
+        plan: NaiveDate,
+        /// Output path, defaults to <PLAN>.ics
+        #[clap(short, long)]
+        output: Option<String>,
+    },
+    /// Open Recipe Format (YAML) file for sharing a recipe with other apps
+    Orf {
+        /// Name of the recipe to export e.g. chili
+        recipe: String,
+        /// Output path, defaults to <RECIPE>.orf.yaml
+        #[clap(short, long)]
+        output: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ImportFormat {
+    /// Spreadsheet with one row per ingredient and columns:
+    /// name, tags, amount, unit, ingredient, step
+    Csv {
+        /// Path to CSV file
+        path: String,
+    },
+    /// Open Recipe Format (YAML) file
+    Orf {
+        /// Path to ORF file
+        path: String,
     },
 }
 
 fn main() {
     let cli = Cli::parse();
-    if let Commands::Add {} = &cli.command {
-        add::add_recipe(&cli.recipe_dir).expect("Failed to add recipe");
-    } else if let Commands::View {} = &cli.command {
-        view::display_recipes(&cli.recipe_dir).expect("Failed to view recipes");
-    } else if let Commands::Plan { date } = &cli.command {
-        plan::plan_week(&cli.recipe_dir, &cli.plan_dir, date).expect("Planning failed");
-    } else if let Commands::Behold { n_plans } = &cli.command {
-        behold::display_plan(&cli.recipe_dir, &cli.plan_dir, n_plans)
-            .expect("Failed to Behold meal plan")
-    } else {
-        panic!("At the Disco")
+    if let Err(e) = run(&cli) {
+        let category = e.category();
+        if category == ErrorCategory::Cancelled {
+            // dialoguer hides the cursor while selecting and does not restore it when interrupted
+            let _ = Terminal::stderr().show_cursor();
+        } else {
+            eprintln!("{} {e}", "error:".red().bold());
+        }
+        process::exit(category.exit_code());
+    }
+}
+
+/// Runs a subcommand, all errors are reported by `main`
+fn run(cli: &Cli) -> Result<(), AverseError> {
+    let config = Config::load()?;
+    config.apply_color();
+    set_json_output(cli.json);
+    let recipe_dir = cli.recipe_dir.clone().unwrap_or(config.recipe_dir.clone());
+    let plan_dir = cli.plan_dir.clone().unwrap_or(config.plan_dir.clone());
+    let template_dir = cli
+        .template_dir
+        .clone()
+        .unwrap_or(config.template_dir.clone());
+    let household = config.household();
+    match &cli.command {
+        Commands::Add {
+            name,
+            tag,
+            servings,
+            ingredient,
+            step,
+            from_file,
+            force,
+        } => {
+            if let Some(path) = from_file {
+                add::add_recipe_from_file(&recipe_dir, path, force)?
+            } else if let Some(name) = name {
+                add::add_recipe_from_args(
+                    &recipe_dir,
+                    name,
+                    tag,
+                    servings,
+                    ingredient,
+                    step,
+                    force,
+                )?
+            } else {
+                add::add_recipe(&recipe_dir, &config.units, force)?
+            }
+        }
+        Commands::View { query } => view::display_recipes(&recipe_dir, query)?,
+        Commands::Show {
+            recipe,
+            ingredients,
+            steps,
+            servings,
+            scale,
+        } => show::show_recipe(&recipe_dir, recipe, ingredients, steps, servings, scale)?,
+        Commands::List {
+            query,
+            tag,
+            without_tag,
+            ingredient,
+            sort,
+        } => {
+            let query = query
+                .clone()
+                .and(filter_query(tag, without_tag, ingredient));
+            list::list_recipes(&recipe_dir, &plan_dir, &query, sort)?
+        }
+        Commands::Plan {
+            action: Some(PlanAction::Edit { plan, query }),
+            ..
+        } => plan::edit_plan(&recipe_dir, &plan_dir, plan, query)?,
+        Commands::Plan {
+            action: Some(PlanAction::Cooked { plan }),
+            ..
+        } => plan::mark_cooked(&recipe_dir, &plan_dir, plan, &household)?,
+        Commands::Plan {
+            action: None,
+            date,
+            days,
+            meal,
+            leftovers,
+            from_file,
+            template,
+            copy_from,
+            query,
+            auto,
+            slot,
+            require,
+            max_repeats,
+            avoid_recent,
+            max_groceries,
+        } => {
+            let week_start = &config.week_start;
+            let start = date.unwrap_or_else(|| upcoming_week(*week_start, today()));
+            let n_days = days.unwrap_or(plan::PLAN_DAYS);
+            if let Some(path) = from_file {
+                plan::plan_week_from_file(
+                    &recipe_dir,
+                    &plan_dir,
+                    date,
+                    days,
+                    week_start,
+                    path,
+                    &household,
+                )?
+            } else if let Some(name) = template {
+                let template = Template::load(&template_dir, name)?;
+                plan::plan_week_from_template(
+                    &recipe_dir,
+                    &plan_dir,
+                    &start,
+                    days,
+                    query,
+                    &template,
+                    &household,
+                )?
+            } else if let Some(source) = copy_from {
+                plan::plan_week_from_copy(
+                    &recipe_dir,
+                    &plan_dir,
+                    source,
+                    &start,
+                    days,
+                    query,
+                    &household,
+                )?
+            } else if *auto {
+                let mut rules = config.auto.clone();
+                if !slot.is_empty() {
+                    rules.slots = slot.clone();
+                }
+                rules.require.extend(require.iter().cloned());
+                rules.max_repeats = max_repeats.unwrap_or(rules.max_repeats);
+                rules.avoid_recent = avoid_recent.unwrap_or(rules.avoid_recent);
+                rules.max_groceries = max_groceries.or(rules.max_groceries);
+                plan::plan_week_auto(
+                    &recipe_dir,
+                    &plan_dir,
+                    &start,
+                    &n_days,
+                    query,
+                    &rules,
+                    &household,
+                )?
+            } else if !meal.is_empty() {
+                plan::plan_week_from_meals(
+                    &recipe_dir,
+                    &plan_dir,
+                    &start,
+                    &n_days,
+                    meal,
+                    leftovers,
+                    &household,
+                )?
+            } else {
+                plan::plan_week(&recipe_dir, &plan_dir, &start, &n_days, query, &household)?
+            }
+        }
+        Commands::History { forgotten_after } => {
+            history::show_history(&plan_dir, &today(), forgotten_after)?
+        }
+        Commands::Pantry { action } => match action {
+            PantryAction::Add { ingredient } => {
+                pantry::add_to_pantry(&household.pantry_file, ingredient)?
+            }
+            PantryAction::Remove { ingredient } => {
+                pantry::remove_from_pantry(&household.pantry_file, ingredient)?
+            }
+            PantryAction::List => pantry::list_pantry(&household.pantry_file)?,
+        },
+        Commands::Template { action } => match action {
+            TemplateAction::Save { name, plan, force } => {
+                template::save_template(&recipe_dir, &plan_dir, &template_dir, name, plan, force)?
+            }
+            TemplateAction::List => template::list_templates(&template_dir)?,
+        },
+        Commands::Groceries { plan, from, to } => {
+            groceries::combined_groceries(&recipe_dir, &plan_dir, plan, from, to, &household)?
+        }
+        Commands::Behold { plan, n_plans } => {
+            let n_plans = n_plans.unwrap_or(config.n_plans);
+            behold::display_plan(&recipe_dir, &plan_dir, plan, &n_plans, &config.week_start)?
+        }
+        Commands::Print { plan, html, output } => {
+            print::print_plan(&recipe_dir, &plan_dir, plan, html, output, &household)?
+        }
+        Commands::Export { format } => match format {
+            ExportFormat::Ics { plan, output } => {
+                export::export_ics(&recipe_dir, &plan_dir, plan, output)?
+            }
+            ExportFormat::Orf { recipe, output } => {
+                export::export_orf(&recipe_dir, recipe, output)?
+            }
+        },
+        Commands::Import { format } => match format {
+            ImportFormat::Csv { path } => import::import_csv(&recipe_dir, path)?,
+            ImportFormat::Orf { path } => import::import_orf(&recipe_dir, path)?,
+        },
+        Commands::Completions { shell } => completions::print_completions(
+            Cli::command(),
+            *shell,
+            &recipe_dir,
+            &plan_dir,
+            &template_dir,
+        )?,
     }
+    Ok(())
 }
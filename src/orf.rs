@@ -0,0 +1,161 @@
+//! Conversions to and from the YAML-based Open Recipe Format (ORF)
+//!
+//! See <https://github.com/OpenRecipeFormat/OpenRecipeFormat> for the schema.
+//! ORF has no field for tags, so they are stored as a `notes` entry beginning with `Tags:`.
+use crate::errors::IngredientParsingError;
+use crate::{Ingredient, Recipe, Unit};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// Prefix of the ORF note used to carry recipe tags
+const TAGS_NOTE: &str = "Tags:";
+
+/// Recipe in Open Recipe Format
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrfRecipe {
+    pub recipe_name: String,
+    /// List of single-entry maps from yield unit to amount, e.g. `servings: 4`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub yields: Vec<BTreeMap<String, f32>>,
+    /// List of single-entry maps from ingredient name to its details
+    #[serde(default)]
+    pub ingredients: Vec<BTreeMap<String, OrfIngredient>>,
+    #[serde(default)]
+    pub steps: Vec<OrfStep>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+}
+
+/// Ingredient details, only the first amount is used on import
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OrfIngredient {
+    #[serde(default)]
+    pub amounts: Vec<OrfAmount>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub processing: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+}
+
+/// Amount of an ingredient with a free-text unit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrfAmount {
+    pub amount: f32,
+    pub unit: String,
+}
+
+/// Single recipe step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrfStep {
+    pub step: String,
+}
+
+impl From<&Recipe> for OrfRecipe {
+    fn from(recipe: &Recipe) -> Self {
+        let ingredients = recipe
+            .ingredients
+            .iter()
+            .map(|ingr| {
+                let details = OrfIngredient {
+                    amounts: vec![OrfAmount {
+                        amount: ingr.amount,
+                        unit: orf_unit(&ingr.unit).into(),
+                    }],
+                    ..Default::default()
+                };
+                BTreeMap::from([(ingr.name.clone(), details)])
+            })
+            .collect();
+        let steps = recipe
+            .steps
+            .iter()
+            .map(|x| OrfStep { step: x.clone() })
+            .collect();
+        let notes = match recipe.tags.is_empty() {
+            true => vec![],
+            false => vec![format!("{TAGS_NOTE} {}", recipe.tags.join(", "))],
+        };
+        OrfRecipe {
+            recipe_name: recipe.name.clone(),
+            yields: recipe
+                .servings
+                .map(|x| vec![BTreeMap::from([("servings".to_string(), x as f32)])])
+                .unwrap_or_default(),
+            ingredients,
+            steps,
+            notes,
+        }
+    }
+}
+
+impl TryFrom<OrfRecipe> for Recipe {
+    type Error = IngredientParsingError;
+    fn try_from(orf: OrfRecipe) -> Result<Self, Self::Error> {
+        let ingredients = orf
+            .ingredients
+            .into_iter()
+            .flat_map(|x| x.into_iter())
+            .map(|(name, details)| {
+                let (amount, unit) = match details.amounts.first() {
+                    Some(x) => (x.amount, unit_from_orf(&x.unit)?),
+                    None => (1.0, Unit::Item),
+                };
+                Ok(Ingredient { name, amount, unit })
+            })
+            .collect::<Result<Vec<Ingredient>, Self::Error>>()?;
+        let tags = orf
+            .notes
+            .iter()
+            .filter_map(|x| x.strip_prefix(TAGS_NOTE))
+            .flat_map(|x| x.split(','))
+            .map(|x| x.trim().to_string())
+            .filter(|x| !x.is_empty())
+            .collect();
+        let servings = orf
+            .yields
+            .iter()
+            .find_map(|x| x.get("servings"))
+            .map(|x| x.round() as u16);
+        Ok(Recipe {
+            name: orf.recipe_name,
+            tags,
+            servings,
+            ingredients,
+            steps: orf.steps.into_iter().map(|x| x.step).collect(),
+        })
+    }
+}
+
+/// ORF unit string for a `Unit`
+fn orf_unit(unit: &Unit) -> &'static str {
+    match *unit {
+        Unit::Can => "can",
+        Unit::Cup => "cup",
+        Unit::Gallon => "gallon",
+        Unit::Gram => "g",
+        Unit::Item => "each",
+        Unit::Kg => "kg",
+        Unit::Lb => "lb",
+        Unit::Oz => "oz",
+        Unit::Tbsp => "tbsp",
+        Unit::Tsp => "tsp",
+    }
+}
+
+/// Parses the free-text units used in ORF files, accepting common spellings and plurals
+pub fn unit_from_orf(unit: &str) -> Result<Unit, IngredientParsingError> {
+    match unit.trim().to_lowercase().trim_end_matches('.') {
+        "" | "each" | "ea" | "whole" | "items" | "piece" | "pieces" => Ok(Unit::Item),
+        "cans" => Ok(Unit::Can),
+        "cups" | "c" => Ok(Unit::Cup),
+        "gallons" | "gal" => Ok(Unit::Gallon),
+        "g" | "gr" | "grams" => Ok(Unit::Gram),
+        "kgs" | "kilogram" | "kilograms" => Ok(Unit::Kg),
+        "lbs" | "pound" | "pounds" => Ok(Unit::Lb),
+        "ounce" | "ounces" => Ok(Unit::Oz),
+        "tablespoon" | "tablespoons" | "tbs" => Ok(Unit::Tbsp),
+        "teaspoon" | "teaspoons" => Ok(Unit::Tsp),
+        other => Unit::from_str(other),
+    }
+}
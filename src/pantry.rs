@@ -0,0 +1,143 @@
+//! Module for tracking ingredients on hand, which offset grocery lists
+//!
+//! The pantry is a YAML file set by `pantry-file` in `averse.toml`, by default `pantry.yaml`
+//! beside the config. It lists ingredients in the same form as recipes, e.g.
+//!
+//! ```yaml
+//! - name: kidney beans
+//!   amount: 2.0
+//!   unit: Can
+//! ```
+//!
+//! Stock is matched to groceries by ingredient name (case-insensitive) and unit.
+//! Marking a plan cooked with `plan cooked` offers to deduct its groceries from the pantry
+use crate::errors::{PantryError, RecipeParsingError};
+use crate::utils::{json_output, print_json, print_table, read_yaml, write_file};
+use crate::{Ingredient, IngredientRow};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Ingredients on hand, see the module docs for the file format
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Pantry {
+    items: Vec<Ingredient>,
+}
+
+impl Pantry {
+    /// Loads the pantry, which is empty if the file doesn't exist yet
+    pub fn load(path: &Path) -> Result<Self, RecipeParsingError> {
+        match path.exists() {
+            true => read_yaml(path),
+            false => Ok(Pantry::default()),
+        }
+    }
+
+    /// Writes the pantry to disk
+    pub fn write(&self, path: &Path) -> Result<(), RecipeParsingError> {
+        write_file(path, &serde_yaml::to_string(&self)?)
+    }
+
+    /// Ingredients on hand, sorted by name
+    pub fn items(&self) -> &Vec<Ingredient> {
+        &self.items
+    }
+
+    /// Amount on hand of an ingredient in the same unit
+    pub fn have(&self, ingr: &Ingredient) -> f32 {
+        self.items
+            .iter()
+            .filter(|x| x.key() == ingr.key())
+            .fold(0.0, |total, x| total + x.amount)
+    }
+
+    /// Adds stock of an ingredient
+    pub fn add(&mut self, ingr: Ingredient) {
+        match self.items.iter_mut().find(|x| x.key() == ingr.key()) {
+            Some(item) => item.amount += ingr.amount,
+            None => self.items.push(ingr),
+        }
+        self.items.sort_by_key(|x| x.name.to_lowercase());
+    }
+
+    /// Removes up to the amount of an ingredient, dropping it once none is left.
+    /// Returns the amount removed
+    pub fn remove(&mut self, ingr: &Ingredient) -> f32 {
+        let removed = match self.items.iter_mut().find(|x| x.key() == ingr.key()) {
+            Some(item) => {
+                let removed = item.amount.min(ingr.amount);
+                item.amount -= removed;
+                removed
+            }
+            None => 0.0,
+        };
+        self.items.retain(|x| x.amount > 0.0);
+        removed
+    }
+
+    /// Removes every unit of an ingredient by name, returning false if there is none
+    pub fn remove_all(&mut self, name: &str) -> bool {
+        let len = self.items.len();
+        self.items
+            .retain(|x| x.name.trim().to_lowercase() != name.trim().to_lowercase());
+        self.items.len() < len
+    }
+
+    /// Removes the amounts used by a set of groceries
+    pub fn deduct(&mut self, used: &[Ingredient]) {
+        used.iter().for_each(|x| {
+            self.remove(x);
+        });
+    }
+}
+
+/// Logic for adding ingredients in the form `<AMOUNT> <UNIT> <INGREDIENT>` to the pantry
+pub fn add_to_pantry(path: &Path, ingredients: &[String]) -> Result<(), PantryError> {
+    let mut pantry = Pantry::load(path)?;
+    for ingr in ingredients {
+        pantry.add(parse_ingredient(ingr)?);
+    }
+    pantry.write(path)?;
+    list_pantry(path)
+}
+
+/// Logic for removing ingredients from the pantry, either an amount in the form
+/// `<AMOUNT> <UNIT> <INGREDIENT>` or every unit of an ingredient by name
+pub fn remove_from_pantry(path: &Path, ingredients: &[String]) -> Result<(), PantryError> {
+    let mut pantry = Pantry::load(path)?;
+    for ingr in ingredients {
+        let found = match ingr.parse::<Ingredient>() {
+            Ok(parsed) => pantry.remove(&parsed) > 0.0,
+            Err(_) => pantry.remove_all(ingr),
+        };
+        if !found {
+            return Err(PantryError::NotInPantry(ingr.clone()));
+        }
+    }
+    pantry.write(path)?;
+    list_pantry(path)
+}
+
+/// Logic for listing the ingredients on hand
+pub fn list_pantry(path: &Path) -> Result<(), PantryError> {
+    let pantry = Pantry::load(path)?;
+    if json_output() {
+        print_json(pantry.items())?;
+        return Ok(());
+    }
+    let rows = pantry
+        .items()
+        .iter()
+        .cloned()
+        .map(IngredientRow::try_from)
+        .collect::<Result<Vec<IngredientRow>, _>>()?;
+    print_table(&rows);
+    Ok(())
+}
+
+/// Parses an ingredient, keeping the input for the error message
+fn parse_ingredient(input: &str) -> Result<Ingredient, PantryError> {
+    input
+        .parse()
+        .map_err(|e| PantryError::InvalidIngredient(input.into(), e))
+}
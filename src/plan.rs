@@ -1,43 +1,611 @@
-//! Module for planning recipes for the week
-use crate::errors::RecipeParsingError;
+//! Module for planning recipes for the week, or any other span of days
+use crate::auto::{Generator, Rules};
+use crate::category::Categories;
+use crate::config::Household;
+use crate::date::{next_weekday, parse_weekday, today, upcoming_week, DATE_FORMAT};
+use crate::errors::{DateError, PlanError, RecipeParsingError};
+use crate::history::{forgotten, recipe_history, FORGOTTEN_AFTER, SUGGESTIONS};
+use crate::pantry::Pantry;
+use crate::query::Query;
+use crate::template::{Entry, Template};
 use crate::utils::{
-    fuzzy_select, get_recipe_out_path, print_table, recipe_name_from_summary, summarize_recipes,
-    title,
+    confirm, fuzzy_select, get_jsons, get_plan_path, get_recipe_out_path, json_output, notify,
+    notify_saved, print_json, read_yaml, search_recipes, select, title, write_file,
 };
 use crate::{GroceryRow, Ingredient, PlanRow, Recipe, WEEK};
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
 use colored::*;
-use dialoguer::Confirm;
+use rand::seq::SliceRandom;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt::{Display, Formatter, Result as FmtResult};
-use std::fs;
 use std::io;
-use std::path::Path;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tabled::builder::Builder;
 use tabled::{object::Columns, Format, Modify, Style, Table};
 
-/// Logic for week planning
+/// Number of days covered by a plan unless given otherwise
+pub const PLAN_DAYS: u16 = 7;
+
+/// Flags for planning without prompts, suggested when `--json` meets a prompt
+const PLAN_FLAGS: &str = "use --meal, --from-file, or --auto";
+
+/// Logic for week planning, recipes to pick from are narrowed down by `query`
 pub fn plan_week(
-    recipe_dir: &String,
-    plan_dir: &String,
-    date: &String,
-) -> Result<(), RecipeParsingError> {
+    recipe_dir: &str,
+    plan_dir: &str,
+    date: &NaiveDate,
+    days: &u16,
+    query: &Query,
+    household: &Household,
+) -> Result<(), PlanError> {
+    if json_output() {
+        return Err(PlanError::NoPrompt(PLAN_FLAGS));
+    }
     title("\t\u{21F8} Plan\n\n");
-    Plan::new(date, recipe_dir, plan_dir)
-        .add_recipes()?
-        .compile_groceries()
-        .print_grocery_list()
+    Plan::new(*date, last_day(*date, *days), recipe_dir, plan_dir)?
+        .add_recipes(query, &household.size)?
+        .compile_groceries()?
+        .print_grocery_list(household)?
+        .write()?;
+    Ok(())
+}
+
+/// Logic for filling a plan automatically under `rules`, recipes are narrowed down by `query`.
+/// The plan is shown to accept, reroll, or swap a day before it is written
+pub fn plan_week_auto(
+    recipe_dir: &str,
+    plan_dir: &str,
+    date: &NaiveDate,
+    days: &u16,
+    query: &Query,
+    rules: &Rules,
+    household: &Household,
+) -> Result<(), PlanError> {
+    let recent = match rules.avoid_recent {
+        0 => HashSet::new(),
+        n => latest_plans(plan_dir, &n)?
+            .iter()
+            .flat_map(|x| x.meals().map(|(_, _, meal)| meal.recipe().clone()))
+            .collect(),
+    };
+    let generator = Generator::new(search_recipes(recipe_dir, query)?, &recent, rules)?;
+    let mut rng = rand::thread_rng();
+    let mut plan = Plan::new(*date, last_day(*date, *days), recipe_dir, plan_dir)?;
+    plan.recipes = generator.fill(&mut rng, &plan.days())?;
+    let mut status = None;
+    while !json_output() {
+        title("\t\u{21F8} Plan\n\n");
+        println!("{plan}");
+        if let Some(e) = status.take() {
+            notify(&format!("{e}"));
+        }
+        let result = match select(&["Accept", "Reroll", "Swap a day"])? {
+            0 => break,
+            1 => generator.fill(&mut rng, &plan.days()),
+            _ => generator.swap(&mut rng, plan.select_day()?, &plan.recipes),
+        };
+        match result {
+            Ok(recipes) => plan.recipes = recipes,
+            Err(e) => status = Some(e),
+        }
+    }
+    plan.compile_groceries()?
+        .print_grocery_list(household)?
+        .write()?;
+    Ok(())
+}
+
+/// Logic for week planning from a template, see the `template` module.
+/// Template queries are resolved to a random matching recipe, preferring recipes not yet planned.
+/// Only open slots are prompted for, picking from recipes matching `query`.
+/// `days` takes precedence over the template's span, falling back to a week
+pub fn plan_week_from_template(
+    recipe_dir: &str,
+    plan_dir: &str,
+    date: &NaiveDate,
+    days: &Option<u16>,
+    query: &Query,
+    template: &Template,
+    household: &Household,
+) -> Result<(), PlanError> {
+    let days = days.or(template.days).unwrap_or(PLAN_DAYS);
+    let mut plan = Plan::new(*date, last_day(*date, days), recipe_dir, plan_dir)?;
+    let mut queries = vec![];
+    let mut open = vec![];
+    for (day, slot, entry) in template.entries()? {
+        let day = plan.resolve_day(day)?;
+        match entry {
+            Entry::Meal(meal) => plan.add_meal(day, slot, meal),
+            Entry::Query(query) => queries.push((day, slot, query)),
+            Entry::Open => open.push((day, slot)),
+        }
+    }
+    let mut rng = rand::thread_rng();
+    for (day, slot, query) in queries {
+        let planned = plan
+            .meals()
+            .map(|(_, _, x)| x.recipe().clone())
+            .collect::<HashSet<String>>();
+        let recipes = search_recipes(recipe_dir, &query)?;
+        let (fresh, stale): (Vec<&Recipe>, Vec<&Recipe>) =
+            recipes.iter().partition(|x| !planned.contains(&x.slug()));
+        let recipe = match fresh.is_empty() {
+            true => stale.choose(&mut rng),
+            false => fresh.choose(&mut rng),
+        }
+        .ok_or(PlanError::NoMatch(day, slot))?;
+        plan.add_meal(day, slot, Meal::Cook(recipe.slug()));
+    }
+    if !open.is_empty() {
+        if json_output() {
+            return Err(PlanError::NoPrompt(PLAN_FLAGS));
+        }
+        let recipes = search_recipes(recipe_dir, query)?;
+        if recipes.is_empty() {
+            return Err(PlanError::NoRecipes);
+        }
+        let summaries = recipes.iter().map(|x| x.summary()).collect::<Vec<String>>();
+        for (day, slot) in open {
+            title(&format!(
+                "\t\u{21F8} Plan\n\n{slot} for {}",
+                day.format("%A %Y-%m-%d")
+            ));
+            println!("{plan}");
+            let recipe = &recipes[fuzzy_select(&summaries)?];
+            plan.add_meal(day, slot, Meal::Cook(recipe.slug()));
+        }
+    }
+    let notes = template.notes.clone().into_iter().collect();
+    plan.set_days(HashMap::new(), notes)?
+        .validate()?
+        .compile_groceries()?
+        .print_grocery_list(household)?
+        .write()?;
+    Ok(())
+}
+
+/// Logic for copying the plan starting on `source` to `date`, keeping the original file.
+/// Meals and notes keep their offset from the first day, `days` takes precedence over the
+/// original span. The copy is shown to add recipes to or clear days before it is written
+pub fn plan_week_from_copy(
+    recipe_dir: &str,
+    plan_dir: &str,
+    source: &NaiveDate,
+    date: &NaiveDate,
+    days: &Option<u16>,
+    query: &Query,
+    household: &Household,
+) -> Result<(), PlanError> {
+    if source == date {
+        return Err(PlanError::CopyOntoSelf(*date));
+    }
+    let mut plan = Plan::load(source, recipe_dir, plan_dir)?.shifted(*date, days)?;
+    while !json_output() {
+        title("\t\u{21F8} Plan\n\n");
+        println!("{plan}");
+        match select(&["Accept", "Add recipes", "Clear a day"])? {
+            0 => break,
+            1 => {
+                plan.add_recipes(query, &household.size)?;
+            }
+            _ => {
+                let day = plan.select_day()?;
+                plan.recipes.remove(&day);
+            }
+        }
+    }
+    plan.validate()?
+        .compile_groceries()?
+        .print_grocery_list(household)?
+        .write()?;
+    Ok(())
+}
+
+/// Logic for editing an existing plan. Meals are moved to another day, removed, or replaced
+/// with recipes matching `query`, and days are swapped. The changes to meals and groceries
+/// are shown before the plan is saved
+pub fn edit_plan(
+    recipe_dir: &str,
+    plan_dir: &str,
+    date: &NaiveDate,
+    query: &Query,
+) -> Result<(), PlanError> {
+    if json_output() {
+        return Err(PlanError::NoPrompt(
+            "use --meal or --from-file to plan the dates again",
+        ));
+    }
+    let mut original = Plan::load(date, recipe_dir, plan_dir)?;
+    original.compile_groceries()?;
+    let mut plan = original.clone();
+    let recipes = search_recipes(recipe_dir, query)?;
+    let summaries = recipes.iter().map(|x| x.summary()).collect::<Vec<String>>();
+    let mut status: Option<String> = None;
+    loop {
+        title("\t\u{21F8} Plan\n\nEdit");
+        println!("{plan}");
+        if let Some(msg) = status.take() {
+            notify(&msg);
+        }
+        let action = select(&[
+            "Done",
+            "Move a meal",
+            "Remove a meal",
+            "Replace a meal",
+            "Swap two days",
+        ])?;
+        let has_meals = plan.meals().next().is_some();
+        match action {
+            0 => match plan.validate() {
+                Ok(_) => break,
+                Err(e) => status = Some(e.to_string()),
+            },
+            1..=3 if !has_meals => status = Some("No meals are planned".into()),
+            1 => {
+                let (day, slot, idx) = plan.select_meal()?;
+                plan.move_meal(day, slot, idx, plan.select_day()?);
+            }
+            2 => {
+                let (day, slot, idx) = plan.select_meal()?;
+                plan.remove_meal(day, slot, idx);
+            }
+            3 if recipes.is_empty() => status = Some(PlanError::NoRecipes.to_string()),
+            3 => {
+                let (day, slot, idx) = plan.select_meal()?;
+                let recipe = &recipes[fuzzy_select(&summaries)?];
+                plan.replace_meal(day, slot, idx, Meal::Cook(recipe.slug()));
+            }
+            _ => plan.swap_days(plan.select_day()?, plan.select_day()?),
+        }
+    }
+    plan.compile_groceries()?;
+    let changes = original.diff(&plan);
+    for line in &changes {
+        match line.starts_with('-') {
+            true => println!("{}", line.red()),
+            false => println!("{}", line.green()),
+        }
+    }
+    if changes.is_empty() {
+        notify("No changes to save");
+    } else if confirm("Save changes?")? {
+        plan.write()?;
+    }
+    Ok(())
+}
+
+/// Logic for marking a plan cooked, offering to deduct its groceries from the pantry
+pub fn mark_cooked(
+    recipe_dir: &str,
+    plan_dir: &str,
+    date: &NaiveDate,
+    household: &Household,
+) -> Result<(), PlanError> {
+    let mut plan = Plan::load(date, recipe_dir, plan_dir)?;
+    if plan.cooked {
+        notify(&format!("{} is already marked cooked", plan.title()));
+        return Ok(());
+    }
+    plan.cooked = true;
+    plan.compile_groceries()?;
+    let path = &household.pantry_file;
+    let mut pantry = Pantry::load(path)?;
+    let stocked = plan
+        .groceries
+        .iter()
+        .filter(|x| pantry.have(x) > 0.0)
+        .count();
+    if stocked > 0 && json_output() {
+        notify("Pantry not deducted - run without --json to deduct the groceries used");
+    } else if stocked > 0
+        && confirm(&format!(
+            "{stocked} of the plan's groceries are in the pantry. Deduct the amounts used?"
+        ))?
+    {
+        pantry.deduct(&plan.groceries);
+        pantry.write(path)?;
+    }
+    plan.write()?;
+    Ok(())
+}
+
+/// Logic for week planning from `DAY[:SLOT]=RECIPE` arguments without prompting.
+/// Recipes in `leftovers` are eaten as leftovers of an earlier cook
+pub fn plan_week_from_meals(
+    recipe_dir: &str,
+    plan_dir: &str,
+    date: &NaiveDate,
+    days: &u16,
+    meals: &[String],
+    leftovers: &[String],
+    household: &Household,
+) -> Result<(), PlanError> {
+    let mut recipes: HashMap<String, HashMap<String, Vec<Meal>>> = HashMap::new();
+    let cooks = meals.iter().map(|x| (x, false));
+    for (meal, is_leftovers) in cooks.chain(leftovers.iter().map(|x| (x, true))) {
+        let (day, recipe) = meal
+            .split_once('=')
+            .ok_or_else(|| PlanError::InvalidMeal(meal.into()))?;
+        let (day, slot) = match day.split_once(':') {
+            Some((day, slot)) => (day, slot.to_string()),
+            None => (day, Slot::default().to_string()),
+        };
+        let recipe = recipe.trim().to_string();
+        recipes
+            .entry(day.trim().into())
+            .or_default()
+            .entry(slot)
+            .or_default()
+            .push(match is_leftovers {
+                true => Meal::Leftovers { leftovers: recipe },
+                false => Meal::Cook(recipe),
+            });
+    }
+    Plan::new(*date, last_day(*date, *days), recipe_dir, plan_dir)?
+        .set_days(recipes, HashMap::new())?
+        .validate()?
+        .compile_groceries()?
+        .print_grocery_list(household)?
         .write()?;
     Ok(())
 }
 
-/// Associates recipes with days of the week
+/// Logic for week planning from a YAML plan skeleton, or stdin if `path` is `-`.
+/// The skeleton holds `recipes` and optionally `date`, `end`, and `notes`, keyed by weekday or date.
+/// Recipes are nested under meal slots, or listed directly for the default slot.
+/// Leftovers of an earlier cook are listed as `leftovers: RECIPE`.
+/// `date` and `days` take precedence over the skeleton's span, falling back to the upcoming week
+pub fn plan_week_from_file(
+    recipe_dir: &str,
+    plan_dir: &str,
+    date: &Option<NaiveDate>,
+    days: &Option<u16>,
+    week_start: &Weekday,
+    path: &str,
+    household: &Household,
+) -> Result<(), PlanError> {
+    let skeleton: PlanFile = read_yaml(Path::new(path))?;
+    let date = match (date, &skeleton.date) {
+        (Some(date), _) => *date,
+        (None, Some(date)) => parse_plan_date(date)?,
+        (None, None) => upcoming_week(*week_start, today()),
+    };
+    let end = match (days, &skeleton.end) {
+        (Some(days), _) => last_day(date, *days),
+        (None, Some(end)) => parse_plan_date(end)?,
+        (None, None) => last_day(date, PLAN_DAYS),
+    };
+    Plan::new(date, end, recipe_dir, plan_dir)?
+        .set_days(slots(skeleton.recipes), skeleton.notes)?
+        .validate()?
+        .compile_groceries()?
+        .print_grocery_list(household)?
+        .write()?;
+    Ok(())
+}
+
+/// Loads all plans in a directory
+pub fn load_plans(plan_dir: &str) -> Result<Vec<Plan>, RecipeParsingError> {
+    get_jsons(Path::new(&plan_dir))?
+        .iter()
+        .map(Plan::try_from)
+        .collect()
+}
+
+/// Fetches latest N plans, newest first
+pub fn latest_plans(plan_dir: &str, n_plans: &usize) -> Result<Vec<Plan>, RecipeParsingError> {
+    let mut plans = load_plans(plan_dir)?;
+    plans.sort_by_key(|x| Reverse(x.date));
+    plans.truncate(*n_plans);
+    Ok(plans)
+}
+
+/// Maps each planned recipe to the latest day it is cooked, as in `recipe_history`
+pub fn last_planned(plans: &[Plan]) -> HashMap<String, NaiveDate> {
+    recipe_history(plans)
+        .into_iter()
+        .map(|x| (x.recipe, x.last))
+        .collect()
+}
+
+/// Grocery table rows grouped by category in aisle order, with the amounts on hand and left
+/// to buy
+pub fn grocery_rows(
+    groceries: &[Ingredient],
+    pantry: &Pantry,
+    categories: &Categories,
+) -> Vec<GroceryRow> {
+    categories
+        .group(groceries)
+        .into_iter()
+        .flat_map(|(category, items)| items.into_iter().map(move |x| (category, x)))
+        .enumerate()
+        .map(|(i, (category, ingr))| {
+            let have = pantry.have(ingr);
+            GroceryRow {
+                Id: i,
+                Category: category,
+                Amount: ingr.amount,
+                Have: have,
+                Buy: (ingr.amount - have).max(0.0),
+                Unit: ingr.unit.clone(),
+                Ingredient: ingr.name.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Renders plans as a day by meal slot grid, with one block of rows per week
+/// starting on `week_start`. Only slots planned in any of the plans are shown
+pub fn plan_table(plans: &[Plan], week_start: Weekday) -> Table {
+    let first = week_start.num_days_from_sunday() as usize;
+    let header = (0..7).map(|i| WEEK[(first + i) % 7]);
+    let slots = used_slots(plans);
+    let builder = plans
+        .iter()
+        .flat_map(|plan| plan.weeks(week_start))
+        .flat_map(|week| week.rows(&slots))
+        .fold(
+            Builder::new().set_header(["Plan", "Meal"].into_iter().chain(header)),
+            |builder, row| builder.add_row(row),
+        );
+    builder.build().with(Style::psql())
+}
+
+/// Meal slots with recipes in any of the plans, or the default slot if there are none
+pub fn used_slots(plans: &[Plan]) -> Vec<Slot> {
+    let slots = plans
+        .iter()
+        .flat_map(|x| x.meals())
+        .map(|(_, slot, _)| slot)
+        .collect::<BTreeSet<Slot>>();
+    match slots.is_empty() {
+        true => vec![Slot::default()],
+        false => slots.into_iter().collect(),
+    }
+}
+
+/// Number of days after a cook its leftovers cover, given the servings of the recipe
+/// and the number of people eating each meal
+pub fn leftover_days(servings: u16, household: u16) -> u16 {
+    (servings / household.max(1)).saturating_sub(1)
+}
+
+/// Last day of a plan starting on `date` and covering `days` days
+fn last_day(date: NaiveDate, days: u16) -> NaiveDate {
+    date + Duration::days(days as i64 - 1)
+}
+
+/// Plan as written to disk by any version, days may be dates or weekday names.
+/// Also accepted by `plan --from-file`, where the date is optional
+#[derive(Debug, Deserialize)]
+struct PlanFile {
+    /// Older versions call the date `name`
+    #[serde(default, alias = "name")]
+    date: Option<String>,
+    /// Older versions always plan a week
+    #[serde(default)]
+    end: Option<String>,
+    #[serde(default)]
+    recipes: HashMap<String, DayFile>,
+    #[serde(default)]
+    notes: HashMap<String, String>,
+    #[serde(default)]
+    cooked: bool,
+}
+
+/// Recipes for a day, older versions list them without meal slots
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum DayFile {
+    Recipes(Vec<Meal>),
+    Slots(HashMap<String, Vec<Meal>>),
+}
+
+/// Nests recipes listed without meal slots under the default slot
+fn slots(days: HashMap<String, DayFile>) -> HashMap<String, HashMap<String, Vec<Meal>>> {
+    days.into_iter()
+        .map(|(day, recipes)| match recipes {
+            DayFile::Recipes(names) => (day, HashMap::from([(Slot::default().to_string(), names)])),
+            DayFile::Slots(slots) => (day, slots),
+        })
+        .collect()
+}
+
+/// Meal of the day a recipe is planned for
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Slot {
+    Breakfast,
+    Lunch,
+    #[default]
+    Dinner,
+    Snack,
+}
+
+impl Slot {
+    /// Every slot, in order of the day
+    pub const ALL: [Slot; 4] = [Slot::Breakfast, Slot::Lunch, Slot::Dinner, Slot::Snack];
+}
+
+impl Display for Slot {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let name = match self {
+            Slot::Breakfast => "Breakfast",
+            Slot::Lunch => "Lunch",
+            Slot::Dinner => "Dinner",
+            Slot::Snack => "Snack",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for Slot {
+    type Err = PlanError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Slot::ALL
+            .into_iter()
+            .find(|x| x.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| PlanError::InvalidSlot(s.into()))
+    }
+}
+
+/// Recipe eaten in a meal slot, written to plan files as `RECIPE` or `leftovers: RECIPE`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Meal {
+    /// Recipe cooked for the meal
+    Cook(String),
+    /// Leftovers of an earlier cook of the recipe, which adds no groceries
+    Leftovers { leftovers: String },
+}
+
+impl Meal {
+    /// Name of the recipe eaten
+    pub fn recipe(&self) -> &String {
+        match self {
+            Meal::Cook(name) | Meal::Leftovers { leftovers: name } => name,
+        }
+    }
+
+    pub fn is_leftovers(&self) -> bool {
+        matches!(self, Meal::Leftovers { .. })
+    }
+}
+
+impl Display for Meal {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            Meal::Cook(name) => write!(f, "{name}"),
+            Meal::Leftovers { leftovers } => write!(f, "{leftovers} (leftovers)"),
+        }
+    }
+}
+
+/// Meal slot -> List of meals
+pub type Meals = BTreeMap<Slot, Vec<Meal>>;
+
+/// Associates recipes with the days of a week, or any other span of days
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(try_from = "PlanFile")]
 pub struct Plan {
-    /// Date in the form of YYYY-MM-DD is used as file name
-    pub name: String,
-    /// Day of week -> List of recipe names
-    pub recipes: HashMap<String, Vec<String>>,
+    /// First day of the plan, also used as the file name
+    pub date: NaiveDate,
+    /// Last day of the plan
+    pub end: NaiveDate,
+    /// Day -> Meal slot -> List of recipe names
+    pub recipes: BTreeMap<NaiveDate, Meals>,
+    /// Day -> Short note for the day, e.g. who is cooking
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub notes: BTreeMap<NaiveDate, String>,
+    /// Whether the plan was marked cooked, see `mark_cooked`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cooked: bool,
     /// Contains the distilled set of groceries
     #[serde(skip)]
     groceries: Vec<Ingredient>,
@@ -50,132 +618,455 @@ pub struct Plan {
 }
 
 impl Plan {
-    /// Creates a new Plan given a name, recipe directory, and plan directory
-    fn new(name: &String, recipe_dir: &String, plan_dir: &String) -> Self {
-        Plan {
-            name: name.into(),
+    /// Creates a new Plan given a first and last day, recipe directory, and plan directory
+    fn new(
+        date: NaiveDate,
+        end: NaiveDate,
+        recipe_dir: &str,
+        plan_dir: &str,
+    ) -> Result<Self, PlanError> {
+        if end < date {
+            return Err(PlanError::InvalidSpan(date, end));
+        }
+        Ok(Plan {
+            date,
+            end,
             recipe_dir: recipe_dir.into(),
             plan_dir: plan_dir.into(),
             ..Default::default()
+        })
+    }
+
+    /// Loads an existing plan by date
+    pub fn load(
+        date: &NaiveDate,
+        recipe_dir: &str,
+        plan_dir: &str,
+    ) -> Result<Self, RecipeParsingError> {
+        Ok(Plan {
+            recipe_dir: recipe_dir.into(),
+            plan_dir: plan_dir.into(),
+            ..Plan::try_from(&get_plan_path(plan_dir, &date.to_string()))?
+        })
+    }
+
+    /// Dates covered by the plan, in order
+    pub fn days(&self) -> Vec<NaiveDate> {
+        self.date
+            .iter_days()
+            .take_while(|x| *x <= self.end)
+            .collect()
+    }
+
+    /// Splits the plan into weeks starting on `week_start`, see `plan_table`
+    pub fn weeks(&self, week_start: Weekday) -> Vec<PlanRow> {
+        let first = next_weekday(self.date - Duration::days(6), week_start);
+        first
+            .iter_weeks()
+            .take_while(|x| *x <= self.end)
+            .enumerate()
+            .map(|(i, week)| PlanRow {
+                plan: (i == 0).then_some(self.date),
+                days: week
+                    .iter_days()
+                    .take(7)
+                    .map(|day| {
+                        let names = (self.date..=self.end)
+                            .contains(&day)
+                            .then(|| self.recipes.get(&day).cloned().unwrap_or_default());
+                        (day, names)
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Every planned meal with its day and slot, in order
+    pub fn meals(&self) -> impl Iterator<Item = (NaiveDate, Slot, &Meal)> {
+        self.recipes.iter().flat_map(|(day, meals)| {
+            meals
+                .iter()
+                .flat_map(move |(slot, names)| names.iter().map(move |name| (*day, *slot, name)))
+        })
+    }
+
+    /// Title of the plan, e.g. `Week of 2022-07-31` or `2022-07-31 to 2022-08-13`
+    pub fn title(&self) -> String {
+        match self.days().len() == PLAN_DAYS as usize {
+            true => format!("Week of {}", self.date),
+            false => format!("{} to {}", self.date, self.end),
+        }
+    }
+
+    /// Resolves a day of the plan given as a date, a weekday such as `mon` or `Monday`,
+    /// or an offset from the first day such as `+9`
+    pub fn resolve_day(&self, day: &str) -> Result<NaiveDate, PlanError> {
+        let offset = day
+            .trim()
+            .strip_prefix('+')
+            .and_then(|x| x.parse::<i64>().ok());
+        let date = match (NaiveDate::parse_from_str(day.trim(), DATE_FORMAT), offset) {
+            (Ok(date), _) => date,
+            (Err(_), Some(offset)) => self.date + Duration::days(offset),
+            (Err(_), None) => {
+                let weekday =
+                    parse_weekday(day).ok_or_else(|| PlanError::InvalidDay(day.into()))?;
+                next_weekday(self.date, weekday)
+            }
+        };
+        if date < self.date || date > self.end {
+            return Err(PlanError::InvalidDay(day.into()));
+        }
+        Ok(date)
+    }
+
+    /// Slugs of `recipes` forgotten as of the plan's first day, most planned first.
+    /// See the `history` module
+    fn forgotten(&self, recipes: &[Recipe]) -> Result<Vec<String>, RecipeParsingError> {
+        if !Path::new(&self.plan_dir).is_dir() {
+            return Ok(vec![]);
+        }
+        let history = recipe_history(&load_plans(&self.plan_dir)?);
+        let slugs = recipes
+            .iter()
+            .map(|x| x.slug())
+            .collect::<HashSet<String>>();
+        Ok(forgotten(&history, self.date, FORGOTTEN_AFTER)
+            .into_iter()
+            .filter(|x| slugs.contains(&x.recipe))
+            .map(|x| x.recipe.clone())
+            .collect())
+    }
+
+    /// Prompts for a day of the plan
+    fn select_day(&self) -> io::Result<NaiveDate> {
+        let days = self.days();
+        let labels = days
+            .iter()
+            .map(|x| x.format("%A %Y-%m-%d").to_string())
+            .collect::<Vec<String>>();
+        Ok(days[fuzzy_select(&labels)?])
+    }
+
+    /// Prompts for a meal of the plan, returning its day, slot, and position in the slot
+    fn select_meal(&self) -> io::Result<(NaiveDate, Slot, usize)> {
+        let mut meals: Vec<(NaiveDate, Slot, usize)> = vec![];
+        let labels = self
+            .meals()
+            .map(|(day, slot, meal)| {
+                let idx = meals.iter().filter(|x| (x.0, x.1) == (day, slot)).count();
+                meals.push((day, slot, idx));
+                format!("{} {slot}: {meal}", day.format("%A %Y-%m-%d"))
+            })
+            .collect::<Vec<String>>();
+        Ok(meals[fuzzy_select(&labels)?])
+    }
+
+    /// Removes the meal at position `idx` of a slot, dropping slots and days left empty
+    pub fn remove_meal(&mut self, day: NaiveDate, slot: Slot, idx: usize) -> Option<Meal> {
+        let meals = self.recipes.get_mut(&day)?;
+        let slot_meals = meals.get_mut(&slot).filter(|x| idx < x.len())?;
+        let meal = slot_meals.remove(idx);
+        if slot_meals.is_empty() {
+            meals.remove(&slot);
+        }
+        if meals.is_empty() {
+            self.recipes.remove(&day);
         }
+        Some(meal)
     }
 
-    /// Associates recipes with days of the week
-    fn add_recipes(&mut self) -> io::Result<&mut Self> {
-        let summaries =
-            summarize_recipes(&self.recipe_dir).expect("Failed to fetch recipe summaries");
+    /// Moves the meal at position `idx` of a slot to the same slot on `to`
+    pub fn move_meal(&mut self, day: NaiveDate, slot: Slot, idx: usize, to: NaiveDate) {
+        if let Some(meal) = self.remove_meal(day, slot, idx) {
+            self.add_meal(to, slot, meal);
+        }
+    }
+
+    /// Replaces the meal at position `idx` of a slot
+    pub fn replace_meal(&mut self, day: NaiveDate, slot: Slot, idx: usize, meal: Meal) {
+        let slot_meals = self.recipes.get_mut(&day).and_then(|x| x.get_mut(&slot));
+        if let Some(old) = slot_meals.and_then(|x| x.get_mut(idx)) {
+            *old = meal;
+        }
+    }
+
+    /// Swaps the meals and notes of two days
+    pub fn swap_days(&mut self, a: NaiveDate, b: NaiveDate) {
+        swap_keys(&mut self.recipes, a, b);
+        swap_keys(&mut self.notes, a, b);
+    }
+
+    /// Meals and groceries only in this plan prefixed with `-`, then those only in `edited`
+    /// prefixed with `+`. Groceries are compared as compiled, see `compile_groceries`
+    pub fn diff(&self, edited: &Plan) -> Vec<String> {
+        let meals = |plan: &Plan| {
+            plan.meals()
+                .map(|(day, slot, meal)| format!("{} {slot}: {meal}", day.format("%a %Y-%m-%d")))
+                .collect::<Vec<String>>()
+        };
+        let groceries = |plan: &Plan| {
+            let mut groceries = plan
+                .groceries
+                .iter()
+                .map(|x| format!("Grocery: {x}"))
+                .collect::<Vec<String>>();
+            groceries.sort();
+            groceries
+        };
+        let mut changes = line_changes(&meals(self), &meals(edited));
+        changes.extend(line_changes(&groceries(self), &groceries(edited)));
+        changes
+    }
+
+    /// Copy of the plan starting on `date`, with meals and notes at the same offsets.
+    /// `days` sets the span of the copy, dropping meals past its end
+    fn shifted(&self, date: NaiveDate, days: &Option<u16>) -> Result<Self, PlanError> {
+        let offset = date - self.date;
+        let end = match days {
+            Some(days) => last_day(date, *days),
+            None => self.end + offset,
+        };
+        let mut plan = Plan::new(date, end, &self.recipe_dir, &self.plan_dir)?;
+        plan.recipes = self
+            .recipes
+            .iter()
+            .map(|(day, meals)| (*day + offset, meals.clone()))
+            .filter(|(day, _)| *day <= end)
+            .collect();
+        plan.notes = self
+            .notes
+            .iter()
+            .map(|(day, note)| (*day + offset, note.clone()))
+            .filter(|(day, _)| *day <= end)
+            .collect();
+        Ok(plan)
+    }
+
+    /// Associates recipes with days and meal slots of the plan.
+    /// Offers to plan leftovers when a recipe makes more servings than the `household` eats
+    fn add_recipes(&mut self, query: &Query, household: &u16) -> Result<&mut Self, PlanError> {
+        let recipes = search_recipes(&self.recipe_dir, query)?;
+        if recipes.is_empty() {
+            return Err(PlanError::NoRecipes);
+        }
+        let summaries = recipes.iter().map(|x| x.summary()).collect::<Vec<String>>();
+        // Suggestions are a nicety, unreadable files in the plan directory don't stop planning
+        let (forgotten, skipped) = match self.forgotten(&recipes) {
+            Ok(forgotten) => (forgotten, None),
+            Err(e) => (vec![], Some(format!("No forgotten favorites - {e}"))),
+        };
         loop {
             title("\t\u{21F8} Plan\n\nSelect Day");
-            print_table(&vec![PlanRow::from(self.clone())]);
-            let day_idx = fuzzy_select(&WEEK)?;
-            let recipe_idx = fuzzy_select(&summaries)?;
-            self.recipes
-                .entry(WEEK[day_idx].into())
-                .or_insert(vec![])
-                .push(recipe_name_from_summary(&summaries[recipe_idx]).unwrap());
-            if !Confirm::new()
-                .with_prompt("Add another recipe?")
-                .interact()?
+            if !json_output() {
+                println!("{self}");
+                if let Some(msg) = &skipped {
+                    println!("{}", msg.yellow());
+                }
+                let planned = self
+                    .meals()
+                    .map(|(_, _, x)| x.recipe().clone())
+                    .collect::<HashSet<String>>();
+                let picks = forgotten
+                    .iter()
+                    .filter(|x| !planned.contains(*x))
+                    .take(SUGGESTIONS)
+                    .cloned()
+                    .collect::<Vec<String>>();
+                if !picks.is_empty() {
+                    println!("Forgotten favorites: {}", picks.join(", ").cyan());
+                }
+            }
+            let day = self.select_day()?;
+            let slot = Slot::ALL[select(&Slot::ALL)?];
+            let recipe = &recipes[fuzzy_select(&summaries)?];
+            self.add_meal(day, slot, Meal::Cook(recipe.slug()));
+            let servings = recipe.servings.unwrap_or(0);
+            let extra = leftover_days(servings, *household).min((self.end - day).num_days() as u16);
+            if extra > 0
+                && confirm(&format!(
+                    "{} makes {servings} servings, enough for {} meals. Plan leftovers for the next {extra} days?",
+                    recipe.name,
+                    extra + 1
+                ))?
             {
+                for date in day.iter_days().skip(1).take(extra as usize) {
+                    let leftovers = recipe.slug();
+                    self.add_meal(date, slot, Meal::Leftovers { leftovers });
+                }
+            }
+            if !confirm("Add another recipe?")? {
                 break;
             }
         }
         Ok(self)
     }
 
-    /// Convert Plan to vector of Recipes
-    fn to_recipes(&self) -> Vec<Recipe> {
-        self.recipes
-            .iter()
-            .flat_map(|(_, v)| {
-                v.iter()
-                    .map(|x| Recipe::try_from(&get_recipe_out_path(&self.recipe_dir, x)).unwrap())
-            })
+    /// Adds a meal to a slot of a day
+    fn add_meal(&mut self, day: NaiveDate, slot: Slot, meal: Meal) {
+        let meals = self.recipes.entry(day).or_default();
+        meals.entry(slot).or_default().push(meal);
+    }
+
+    /// Sets recipes and notes keyed by day names or dates, see `resolve_day`.
+    /// Recipes are further keyed by meal slot names
+    fn set_days(
+        &mut self,
+        recipes: HashMap<String, HashMap<String, Vec<Meal>>>,
+        notes: HashMap<String, String>,
+    ) -> Result<&mut Self, PlanError> {
+        for (day, slots) in recipes {
+            let date = self.resolve_day(&day)?;
+            for (slot, meals) in slots {
+                let slot = slot.parse::<Slot>()?;
+                meals.into_iter().for_each(|x| self.add_meal(date, slot, x));
+            }
+        }
+        for (day, note) in notes {
+            let date = self.resolve_day(&day)?;
+            self.notes.insert(date, note);
+        }
+        Ok(self)
+    }
+
+    /// Checks every planned recipe exists and leftovers follow a cook of their recipe
+    fn validate(&mut self) -> Result<&mut Self, PlanError> {
+        let mut cooked: HashSet<&String> = HashSet::new();
+        for (day, _, meal) in self.meals() {
+            if !get_recipe_out_path(&self.recipe_dir, meal.recipe()).exists() {
+                return Err(PlanError::UnknownRecipe(meal.recipe().clone()));
+            }
+            match meal {
+                Meal::Cook(name) => cooked.insert(name),
+                Meal::Leftovers { leftovers } if !cooked.contains(leftovers) => {
+                    return Err(PlanError::NoCook(leftovers.clone(), day));
+                }
+                Meal::Leftovers { .. } => false,
+            };
+        }
+        Ok(self)
+    }
+
+    /// Convert Plan to vector of cooked Recipes, leftovers are skipped
+    fn to_recipes(&self) -> Result<Vec<Recipe>, RecipeParsingError> {
+        self.meals()
+            .filter(|(_, _, x)| !x.is_leftovers())
+            .map(|(_, _, x)| Recipe::try_from(&get_recipe_out_path(&self.recipe_dir, x.recipe())))
             .collect()
     }
 
-    /// Compiles groceries from a list of recipes
-    fn compile_groceries(&mut self) -> &Self {
+    /// Compiles groceries from the cooked recipes, summing amounts of the same ingredient
+    pub fn compile_groceries(&mut self) -> Result<&Self, RecipeParsingError> {
         let mut ingr_map: HashMap<String, Ingredient> = HashMap::new();
-        self.to_recipes().iter().for_each(|recipe: &Recipe| {
+        self.to_recipes()?.iter().for_each(|recipe: &Recipe| {
             recipe.ingredients.iter().for_each(|ingr| {
-                let key = format!("{}_{}", ingr.name, ingr.unit);
-                ingr_map.entry(key).or_insert(ingr.clone());
+                ingr_map
+                    .entry(ingr.key())
+                    .and_modify(|x| x.amount += ingr.amount)
+                    .or_insert(ingr.clone());
             })
         });
-        self.groceries = ingr_map
-            .into_iter()
-            .map(|(_, v)| v)
-            .collect::<Vec<Ingredient>>();
-        self
+        self.groceries = ingr_map.into_values().collect::<Vec<Ingredient>>();
+        Ok(self)
     }
 
-    /// Prints grocery list
-    fn print_grocery_list(&self) -> &Self {
-        let table = Table::new(self.get_grocery_table())
+    /// Distilled set of groceries, see `compile_groceries`
+    pub fn groceries(&self) -> &Vec<Ingredient> {
+        &self.groceries
+    }
+
+    /// Prints grocery list in the household's aisle order, offset by its pantry
+    fn print_grocery_list(&self, household: &Household) -> Result<&Self, RecipeParsingError> {
+        let pantry = Pantry::load(&household.pantry_file)?;
+        let categories = Categories::load(&household.category_file)?;
+        let rows = self.get_grocery_table(&pantry, &categories);
+        if json_output() {
+            print_json(&rows)?;
+            return Ok(self);
+        }
+        let table = Table::new(rows)
             .with(Style::psql())
-            .with(Modify::new(Columns::single(1)).with(Format::new(|s| s.red().to_string())));
+            .with(Modify::new(Columns::single(4)).with(Format::new(|s| s.red().to_string())));
         println!("{table}");
-        self
+        Ok(self)
     }
 
-    /// Generates table from a set of groceries
-    fn get_grocery_table(&self) -> Vec<GroceryRow> {
-        self.groceries
-            .iter()
-            .enumerate()
-            .map(|(i, ingr)| GroceryRow {
-                Id: i,
-                Amount: ingr.amount,
-                Unit: ingr.unit.clone(),
-                Ingredient: ingr.name.clone(),
-            })
-            .collect()
+    /// Generates table from a set of groceries grouped by category in aisle order,
+    /// with the amounts on hand and left to buy
+    pub fn get_grocery_table(&self, pantry: &Pantry, categories: &Categories) -> Vec<GroceryRow> {
+        grocery_rows(&self.groceries, pantry, categories)
     }
 
     /// Write plan to disk
     fn write(&self) -> Result<String, RecipeParsingError> {
-        let outpath = Path::new(&self.plan_dir)
-            .join(&self.name)
-            .with_extension("yaml");
-        let serialized = serde_yaml::to_string(&self)?;
-        fs::write(&outpath, &serialized)?;
-        println!("Recipe saved to {}", outpath.to_str().unwrap());
+        let outpath = get_plan_path(&self.plan_dir, &self.date.to_string());
+        write_file(&outpath, &serde_yaml::to_string(&self)?)?;
+        notify_saved("Plan", &outpath);
         Ok(outpath.to_string_lossy().to_string())
     }
 }
 
+/// Swaps the values of two keys, either of which may be missing
+fn swap_keys<V>(map: &mut BTreeMap<NaiveDate, V>, a: NaiveDate, b: NaiveDate) {
+    let (x, y) = (map.remove(&a), map.remove(&b));
+    if let Some(x) = x {
+        map.insert(b, x);
+    }
+    if let Some(y) = y {
+        map.insert(a, y);
+    }
+}
+
+/// Lines of `old` missing from `new` prefixed with `-`, then lines of `new` missing from `old`
+/// prefixed with `+`. Repeated lines are matched one for one
+fn line_changes(old: &[String], new: &[String]) -> Vec<String> {
+    let mut added = new.to_vec();
+    let mut removed = vec![];
+    for line in old {
+        match added.iter().position(|x| x == line) {
+            Some(idx) => {
+                added.remove(idx);
+            }
+            None => removed.push(format!("- {line}")),
+        }
+    }
+    removed
+        .into_iter()
+        .chain(added.iter().map(|x| format!("+ {x}")))
+        .collect()
+}
+
+/// Parses the date of a plan, which must be in the form YEAR-MONTH-DAY
+fn parse_plan_date(date: &str) -> Result<NaiveDate, DateError> {
+    NaiveDate::parse_from_str(date.trim(), DATE_FORMAT)
+        .map_err(|_| DateError::InvalidDate(date.into()))
+}
+
 impl Display for Plan {
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
-        let table = Table::new(vec![PlanRow::from(self.clone())])
-            .with(Style::psql())
-            .to_string();
+        let table = plan_table(std::slice::from_ref(self), self.date.weekday());
         write!(f, "{table}")
     }
 }
 
-impl TryFrom<&PathBuf> for Plan {
-    type Error = RecipeParsingError;
-    fn try_from(path: &PathBuf) -> Result<Self, Self::Error> {
-        if !path.exists() {
-            panic!("The file does not exist: {path:?}")
-        }
-        Ok(serde_yaml::from_str(&fs::read_to_string(&path)?)?)
+impl TryFrom<PlanFile> for Plan {
+    type Error = PlanError;
+    fn try_from(file: PlanFile) -> Result<Self, Self::Error> {
+        let date = parse_plan_date(&file.date.ok_or(PlanError::NoDate)?)?;
+        let end = match file.end {
+            Some(end) => parse_plan_date(&end)?,
+            None => last_day(date, PLAN_DAYS),
+        };
+        let mut plan = Plan::new(date, end, "", "")?;
+        plan.set_days(slots(file.recipes), file.notes)?;
+        plan.cooked = file.cooked;
+        Ok(plan)
     }
 }
 
-impl From<Plan> for PlanRow {
-    fn from(p: Plan) -> Self {
-        let default = vec![String::from("")];
-        PlanRow {
-            Date: p.name,
-            Sunday: p.recipes.get("Sunday").unwrap_or(&default).join("\n "),
-            Monday: p.recipes.get("Monday").unwrap_or(&default).join("\n "),
-            Tuesday: p.recipes.get("Tuesday").unwrap_or(&default).join("\n "),
-            Wednesday: p.recipes.get("Wednesday").unwrap_or(&default).join("\n "),
-            Thursday: p.recipes.get("Thursday").unwrap_or(&default).join("\n "),
-            Friday: p.recipes.get("Friday").unwrap_or(&default).join("\n "),
-            Saturday: p.recipes.get("Saturday").unwrap_or(&default).join("\n "),
-        }
+impl TryFrom<&PathBuf> for Plan {
+    type Error = RecipeParsingError;
+    fn try_from(path: &PathBuf) -> Result<Self, Self::Error> {
+        read_yaml(path)
     }
 }
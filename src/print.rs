@@ -0,0 +1,210 @@
+//! Module for printing a plan-at-a-glance sheet for the fridge door
+use crate::category::Categories;
+use crate::config::Household;
+use crate::errors::RecipeParsingError;
+use crate::plan::{used_slots, Plan};
+use crate::utils::{json_output, print_json, report_saved, write_file};
+use crate::WEEK;
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+use std::path::Path;
+use tabled::builder::Builder;
+use tabled::{Rotate, Style};
+
+/// Width of the plain-text sheet in columns
+const SHEET_WIDTH: usize = 80;
+
+/// Rendered sheet, printed as JSON when there is no output path
+#[derive(Debug, Serialize)]
+pub struct Sheet {
+    pub sheet: String,
+}
+
+/// Logic for printing a plan as a single printable page
+pub fn print_plan(
+    recipe_dir: &str,
+    plan_dir: &str,
+    plan_date: &NaiveDate,
+    html: &bool,
+    output: &Option<String>,
+    household: &Household,
+) -> Result<(), RecipeParsingError> {
+    let mut plan = Plan::load(plan_date, recipe_dir, plan_dir)?;
+    plan.compile_groceries()?;
+    let categories = Categories::load(&household.category_file)?;
+    let sheet = if *html {
+        plan_to_html(&plan, &categories)
+    } else {
+        plan_to_text(&plan, &categories)
+    };
+    match output {
+        Some(path) => {
+            write_file(Path::new(path), &sheet)?;
+            report_saved("Sheet", Path::new(path))?;
+        }
+        None if json_output() => print_json(&Sheet { sheet })?,
+        None => print!("{sheet}"),
+    }
+    Ok(())
+}
+
+/// Renders a plan with compiled groceries as an 80-column plain-text page.
+/// Groceries are grouped by category in aisle order
+pub fn plan_to_text(plan: &Plan, categories: &Categories) -> String {
+    let (labels, names): (Vec<String>, Vec<String>) = plan
+        .days()
+        .iter()
+        .map(|day| {
+            let meals = plan
+                .recipes
+                .get(day)
+                .iter()
+                .flat_map(|x| x.iter())
+                .flat_map(|(slot, meals)| meals.iter().map(move |x| format!("{slot}: {x}")))
+                .collect::<Vec<String>>();
+            (day.format("%A %m-%d").to_string(), meals.join("\n"))
+        })
+        .unzip();
+    let mut sheet = vec![
+        format!("{:^SHEET_WIDTH$}", plan.title())
+            .trim_end()
+            .to_string(),
+        String::new(),
+        Builder::new()
+            .set_header(labels)
+            .add_row(names)
+            .build()
+            .with(Rotate::Left)
+            .with(Rotate::Bottom)
+            .with(Style::ascii())
+            .to_string(),
+        section("Groceries"),
+    ];
+    let half = SHEET_WIDTH / 2 - 2;
+    for (category, items) in categories.group(plan.groceries()) {
+        sheet.push(format!("{category}"));
+        let items = items
+            .iter()
+            .map(|x| {
+                let item = format!("[ ] {x}");
+                item.chars().take(half).collect::<String>()
+            })
+            .collect::<Vec<String>>();
+        for pair in items.chunks(2) {
+            let line = format!("  {:half$}  {}", pair[0], pair.get(1).unwrap_or(&"".into()));
+            sheet.push(line.trim_end().to_string());
+        }
+    }
+    sheet.push(section("Notes"));
+    for day in plan.days() {
+        let note = plan.notes.get(&day).cloned().unwrap_or_default();
+        sheet.push(format!(
+            "  {:10} {:_<width$}",
+            day.format("%a %m-%d").to_string(),
+            note,
+            width = SHEET_WIDTH - 13
+        ));
+    }
+    sheet.join("\n") + "\n"
+}
+
+/// Renders a plan with compiled groceries as a standalone printable HTML page.
+/// Groceries are grouped by category in aisle order
+pub fn plan_to_html(plan: &Plan, categories: &Categories) -> String {
+    let week_start = plan.date.weekday();
+    let first = week_start.num_days_from_sunday() as usize;
+    let header = (0..7)
+        .map(|i| format!("<th>{}</th>", WEEK[(first + i) % 7]))
+        .collect::<String>();
+    let slots = used_slots(std::slice::from_ref(plan));
+    let weeks = plan
+        .weeks(week_start)
+        .iter()
+        .flat_map(|week| week.rows(&slots))
+        .enumerate()
+        .map(|(i, row)| {
+            // Each week starts with a row of dates, followed by a row per meal slot
+            let cell = if i % (slots.len() + 1) == 0 {
+                "th"
+            } else {
+                "td"
+            };
+            let days = row
+                .iter()
+                .skip(2)
+                .map(|x| format!("<{cell}>{}</{cell}>", escape_html(x).replace('\n', "<br>")))
+                .collect::<String>();
+            format!("<tr><th>{}</th>{days}</tr>", row[1])
+        })
+        .collect::<String>();
+    let groceries = categories
+        .group(plan.groceries())
+        .iter()
+        .map(|(category, items)| {
+            let items = items
+                .iter()
+                .map(|x| format!("<li>&#9744; {}</li>", escape_html(&x.to_string())))
+                .collect::<String>();
+            format!("<section><h3>{category}</h3><ul>{items}</ul></section>")
+        })
+        .collect::<String>();
+    let notes = plan
+        .days()
+        .iter()
+        .map(|day| {
+            let note = plan.notes.get(day).cloned().unwrap_or_default();
+            format!(
+                "<tr><th>{}</th><td>{}</td></tr>",
+                day.format("%A %m-%d"),
+                escape_html(&note)
+            )
+        })
+        .collect::<String>();
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+@page {{ size: letter portrait; margin: 1.5cm; }}
+body {{ font-family: sans-serif; font-size: 10pt; }}
+h1 {{ text-align: center; font-size: 16pt; margin: 0 0 0.5em; }}
+h2 {{ font-size: 12pt; border-bottom: 1px solid #000; margin: 1em 0 0.3em; }}
+h3 {{ font-size: 10pt; margin: 0.3em 0 0.1em; }}
+table {{ width: 100%; border-collapse: collapse; table-layout: fixed; }}
+th, td {{ border: 1px solid #000; padding: 4px; vertical-align: top; text-align: left; }}
+.plan td {{ height: 4em; }}
+.notes th {{ width: 12%; }}
+.notes td {{ height: 1.6em; }}
+.groceries {{ column-count: 3; column-gap: 1.5em; }}
+.groceries section {{ break-inside: avoid; }}
+ul {{ list-style: none; margin: 0; padding: 0; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<table class="plan"><tr><th></th>{header}</tr>{weeks}</table>
+<h2>Groceries</h2>
+<div class="groceries">{groceries}</div>
+<h2>Notes</h2>
+<table class="notes">{notes}</table>
+</body>
+</html>
+"#,
+        title = plan.title(),
+    )
+}
+
+/// Section header for the plain-text sheet
+fn section(name: &str) -> String {
+    format!("\n{name}\n{}", "-".repeat(SHEET_WIDTH))
+}
+
+/// Escapes characters with special meaning in HTML
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,193 @@
+//! Module for searching recipes with a small query language
+//!
+//! Queries are whitespace-separated terms which must all match, e.g.
+//! `tag:soup ingredient:chicken -tag:dessert "slow cooker"`
+//!
+//! - `tag:VALUE`        - Recipe has the tag (case-insensitive)
+//! - `ingredient:VALUE` - An ingredient name contains VALUE, also `ingr:`
+//! - `name:VALUE`       - Recipe name contains VALUE
+//! - `step:VALUE`       - A step contains VALUE
+//! - `VALUE`            - Name, tags, ingredients, or steps contain VALUE
+//! - `-TERM`            - Negates a term
+//! - `A OR B`           - Either side matches, binds looser than the implicit AND
+//!
+//! Values containing spaces are wrapped in double quotes, e.g. `step:"slow cooker"`.
+use crate::errors::QueryError;
+use crate::Recipe;
+use std::str::FromStr;
+
+/// Single condition checked against a recipe
+#[derive(Debug, Clone, PartialEq)]
+pub enum Term {
+    Tag(String),
+    Ingredient(String),
+    Name(String),
+    Step(String),
+    Text(String),
+}
+
+/// Parsed query
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    Term(Term),
+    Not(Box<Query>),
+    And(Vec<Query>),
+    Or(Vec<Query>),
+}
+
+/// Lexical token of a query string
+#[derive(Debug, PartialEq)]
+enum Token {
+    Or,
+    Term { negated: bool, term: Term },
+}
+
+impl Default for Query {
+    /// Empty query, which matches every recipe
+    fn default() -> Self {
+        Query::And(vec![])
+    }
+}
+
+impl Query {
+    /// Returns true if the recipe satisfies the query
+    pub fn matches(&self, recipe: &Recipe) -> bool {
+        match self {
+            Query::Term(term) => term.matches(recipe),
+            Query::Not(query) => !query.matches(recipe),
+            Query::And(queries) => queries.iter().all(|x| x.matches(recipe)),
+            Query::Or(queries) => queries.iter().any(|x| x.matches(recipe)),
+        }
+    }
+
+    /// Combines two queries, both of which must match
+    pub fn and(self, other: Query) -> Query {
+        match (self, other) {
+            (Query::And(mut a), Query::And(b)) => {
+                a.extend(b);
+                Query::And(a)
+            }
+            (Query::And(mut a), b) => {
+                a.push(b);
+                Query::And(a)
+            }
+            (a, b) => Query::And(vec![a, b]),
+        }
+    }
+}
+
+impl Term {
+    /// Returns true if the recipe satisfies the term, comparisons are case-insensitive
+    pub fn matches(&self, recipe: &Recipe) -> bool {
+        let contains = |s: &str, value: &str| s.to_lowercase().contains(value);
+        match self {
+            Term::Tag(value) => recipe.tags.iter().any(|x| x.to_lowercase() == *value),
+            Term::Ingredient(value) => recipe.ingredients.iter().any(|x| contains(&x.name, value)),
+            Term::Name(value) => contains(&recipe.name.replace('-', " "), value),
+            Term::Step(value) => recipe.steps.iter().any(|x| contains(x, value)),
+            Term::Text(value) => {
+                Term::Name(value.clone()).matches(recipe)
+                    || recipe.tags.iter().any(|x| contains(x, value))
+                    || Term::Ingredient(value.clone()).matches(recipe)
+                    || Term::Step(value.clone()).matches(recipe)
+            }
+        }
+    }
+}
+
+impl FromStr for Query {
+    type Err = QueryError;
+    fn from_str(input: &str) -> Result<Query, Self::Err> {
+        let mut groups: Vec<Vec<Query>> = vec![vec![]];
+        for token in tokenize(input)? {
+            match token {
+                Token::Or => {
+                    if groups.last().unwrap().is_empty() {
+                        return Err(QueryError::DanglingOr);
+                    }
+                    groups.push(vec![]);
+                }
+                Token::Term { negated, term } => {
+                    let query = match negated {
+                        true => Query::Not(Box::new(Query::Term(term))),
+                        false => Query::Term(term),
+                    };
+                    groups.last_mut().unwrap().push(query);
+                }
+            }
+        }
+        if groups.len() > 1 && groups.last().unwrap().is_empty() {
+            return Err(QueryError::DanglingOr);
+        }
+        let mut groups = groups
+            .into_iter()
+            .map(|mut x| match x.len() {
+                1 => x.pop().unwrap(),
+                _ => Query::And(x),
+            })
+            .collect::<Vec<Query>>();
+        Ok(match groups.len() {
+            1 => groups.pop().unwrap(),
+            _ => Query::Or(groups),
+        })
+    }
+}
+
+/// Splits a query string into terms and `OR` keywords
+fn tokenize(input: &str) -> Result<Vec<Token>, QueryError> {
+    let mut tokens = vec![];
+    let mut chars = input.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+        let negated = chars.next_if_eq(&'-').is_some();
+        let mut field: Option<String> = None;
+        let mut word = String::new();
+        let mut quoted = false;
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+            if c == '"' {
+                quoted = true;
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    if c == '"' {
+                        closed = true;
+                        break;
+                    }
+                    word.push(c);
+                }
+                if !closed {
+                    return Err(QueryError::UnterminatedQuote);
+                }
+            } else if c == ':' && field.is_none() && !quoted {
+                field = Some(std::mem::take(&mut word));
+            } else {
+                word.push(c);
+            }
+        }
+        if word == "OR" && field.is_none() && !negated && !quoted {
+            tokens.push(Token::Or);
+            continue;
+        }
+        let value = word.trim().to_lowercase();
+        let term = match field {
+            Some(field) => {
+                if value.is_empty() {
+                    return Err(QueryError::EmptyValue(field));
+                }
+                match field.to_lowercase().as_str() {
+                    "tag" => Term::Tag(value),
+                    "ingredient" | "ingr" => Term::Ingredient(value),
+                    "name" => Term::Name(value),
+                    "step" => Term::Step(value),
+                    _ => return Err(QueryError::UnknownField(field)),
+                }
+            }
+            None if value.is_empty() => continue,
+            None => Term::Text(value),
+        };
+        tokens.push(Token::Term { negated, term });
+    }
+    Ok(tokens)
+}
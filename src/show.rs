@@ -0,0 +1,99 @@
+//! Module for showing a single recipe by name
+use crate::errors::ShowError;
+use crate::utils::{json_output, load_recipes, print_json, print_table};
+use crate::{IngredientRow, Recipe, StepRow};
+use strsim::jaro_winkler;
+
+/// Minimum similarity for a recipe to be suggested on a miss
+const SUGGESTION_THRESHOLD: f64 = 0.8;
+
+/// Logic for showing a recipe, optionally scaled and limited to ingredients or steps
+pub fn show_recipe(
+    recipe_dir: &str,
+    name: &str,
+    ingredients_only: &bool,
+    steps_only: &bool,
+    servings: &Option<u16>,
+    scale: &Option<f32>,
+) -> Result<(), ShowError> {
+    let mut recipe = resolve_recipe(recipe_dir, name)?;
+    let factor = match servings {
+        Some(n) => {
+            let base = recipe
+                .servings
+                .filter(|x| *x > 0)
+                .ok_or_else(|| ShowError::NoServings(recipe.name.clone()))?;
+            Some(*n as f32 / base as f32)
+        }
+        None => *scale,
+    };
+    if let Some(factor) = factor {
+        if !(factor.is_finite() && factor > 0.0) {
+            return Err(ShowError::InvalidScale(factor));
+        }
+        recipe = recipe.scale(factor);
+    }
+
+    if json_output() {
+        print_json(&recipe).map_err(|e| ShowError::RecipeError(e.into()))?;
+    } else if *ingredients_only {
+        let rows = recipe
+            .ingredients
+            .into_iter()
+            .map(|x| x.try_into().expect("IngredientRow failed"))
+            .collect::<Vec<IngredientRow>>();
+        print_table(&rows);
+    } else if *steps_only {
+        let rows = recipe
+            .steps
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| StepRow {
+                Step: i as u16 + 1,
+                Details: x,
+            })
+            .collect::<Vec<StepRow>>();
+        print_table(&rows);
+    } else {
+        println!("{recipe}");
+    }
+    Ok(())
+}
+
+/// Finds a recipe by name, slug, or unique prefix of either (case-insensitive).
+/// Similar recipe names are suggested when nothing matches
+pub fn resolve_recipe(recipe_dir: &str, name: &str) -> Result<Recipe, ShowError> {
+    let key = normalize(name);
+    let mut recipes = load_recipes(recipe_dir)?;
+    if let Some(idx) = recipes.iter().position(|x| normalize(&x.name) == key) {
+        return Ok(recipes.swap_remove(idx));
+    }
+    let mut matches = recipes
+        .iter()
+        .enumerate()
+        .filter(|(_, x)| normalize(&x.name).starts_with(&key))
+        .map(|(i, _)| i)
+        .collect::<Vec<usize>>();
+    match matches.len() {
+        1 => Ok(recipes.swap_remove(matches.pop().unwrap())),
+        0 => {
+            let mut scored = recipes
+                .iter()
+                .map(|x| (jaro_winkler(&normalize(&x.name), &key), x.slug()))
+                .filter(|(score, _)| *score >= SUGGESTION_THRESHOLD)
+                .collect::<Vec<(f64, String)>>();
+            scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+            let suggestions = scored.into_iter().take(3).map(|(_, x)| x).collect();
+            Err(ShowError::NotFound(name.into(), suggestions))
+        }
+        _ => Err(ShowError::Ambiguous(
+            name.into(),
+            matches.iter().map(|i| recipes[*i].slug()).collect(),
+        )),
+    }
+}
+
+/// Lowercase slug used to compare recipe names
+fn normalize(name: &str) -> String {
+    name.trim().to_lowercase().replace(' ', "-")
+}
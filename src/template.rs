@@ -0,0 +1,152 @@
+//! Module for saving plans as reusable templates
+//!
+//! Templates are YAML files in the template directory, e.g. `templates/weeknight-rotation.yaml`
+//!
+//! ```yaml
+//! days: 7
+//! recipes:
+//!   friday:
+//!     dinner: [cast-iron-pizza]    # a concrete recipe
+//!   sunday:
+//!     lunch: [veg-fruit-prep]
+//!     dinner: ["?"]                # an open slot, picked when the template is used
+//!   tuesday:
+//!     dinner: ["tag:mexican"]      # any recipe matching a query
+//! ```
+//!
+//! Days are weekday names, or offsets from the first day of the plan such as `+9`.
+//! Entries containing a `:` are queries, see the `query` module for the syntax.
+use crate::errors::{QueryError, RecipeParsingError, TemplateError};
+use crate::plan::{Meal, Meals, Plan, Slot, PLAN_DAYS};
+use crate::query::Query;
+use crate::utils::{
+    check_name, get_jsons, get_plan_path, json_output, notify_saved, print_json, read_yaml,
+    write_file,
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Template entry marking a slot as open
+pub const OPEN: &str = "?";
+
+/// Pattern for plans, see the module docs for the file format
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Template {
+    /// Number of days covered by plans made from the template
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub days: Option<u16>,
+    /// Day -> Meal slot -> List of entries
+    #[serde(default)]
+    pub recipes: BTreeMap<String, Meals>,
+    /// Day -> Short note for the day
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub notes: BTreeMap<String, String>,
+}
+
+/// Meaning of a template entry
+#[derive(Debug, Clone, PartialEq)]
+pub enum Entry {
+    /// Meal planned as-is
+    Meal(Meal),
+    /// Any recipe matching the query
+    Query(Query),
+    /// Recipe picked when the template is used
+    Open,
+}
+
+impl Template {
+    /// Loads a template by name
+    pub fn load(template_dir: &str, name: &str) -> Result<Self, RecipeParsingError> {
+        check_name(name)?;
+        read_yaml(&get_plan_path(template_dir, name))
+    }
+
+    /// Template planning the same meals as a plan, keyed by weekday for plans of up to a week
+    pub fn from_plan(plan: &Plan) -> Self {
+        let days = plan.days().len();
+        let key = |offset: usize| match days <= PLAN_DAYS as usize {
+            true => plan.days()[offset].format("%A").to_string().to_lowercase(),
+            false => format!("+{offset}"),
+        };
+        let offset = |day: NaiveDate| (day - plan.date).num_days() as usize;
+        Template {
+            days: Some(days as u16),
+            recipes: plan
+                .recipes
+                .iter()
+                .map(|(day, meals)| (key(offset(*day)), meals.clone()))
+                .collect(),
+            notes: plan
+                .notes
+                .iter()
+                .map(|(day, note)| (key(offset(*day)), note.clone()))
+                .collect(),
+        }
+    }
+
+    /// Every entry with its day and meal slot
+    pub fn entries(&self) -> Result<Vec<(&String, Slot, Entry)>, QueryError> {
+        let mut entries = vec![];
+        for (day, meals) in &self.recipes {
+            for (slot, meal) in meals
+                .iter()
+                .flat_map(|(s, x)| x.iter().map(move |x| (s, x)))
+            {
+                let entry = match meal {
+                    Meal::Cook(name) if name.trim() == OPEN => Entry::Open,
+                    Meal::Cook(query) if query.contains(':') => Entry::Query(query.parse()?),
+                    meal => Entry::Meal(meal.clone()),
+                };
+                entries.push((day, *slot, entry));
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// Logic for saving a plan as a named template
+pub fn save_template(
+    recipe_dir: &str,
+    plan_dir: &str,
+    template_dir: &str,
+    name: &str,
+    plan_date: &NaiveDate,
+    force: &bool,
+) -> Result<(), TemplateError> {
+    check_name(name)?;
+    let plan = Plan::load(plan_date, recipe_dir, plan_dir)?;
+    let outpath = get_plan_path(template_dir, name);
+    if outpath.exists() && !force {
+        return Err(TemplateError::AlreadyExists(name.into()));
+    }
+    let template = Template::from_plan(&plan);
+    fs::create_dir_all(template_dir)?;
+    write_file(&outpath, &serde_yaml::to_string(&template)?)?;
+    notify_saved("Template", &outpath);
+    if json_output() {
+        print_json(&template)?;
+    }
+    Ok(())
+}
+
+/// Logic for listing the names of saved templates
+pub fn list_templates(template_dir: &str) -> Result<(), TemplateError> {
+    let mut names = match Path::new(template_dir).is_dir() {
+        true => get_jsons(Path::new(template_dir))?
+            .iter()
+            .filter_map(|x| x.file_stem())
+            .map(|x| x.to_string_lossy().to_string())
+            .collect::<Vec<String>>(),
+        false => vec![],
+    };
+    names.sort();
+    if json_output() {
+        print_json(&names)?;
+        return Ok(());
+    }
+    names.iter().for_each(|x| println!("{x}"));
+    Ok(())
+}
@@ -1,25 +1,83 @@
 //! Collection of utility functions
+use crate::query::Query;
 use crate::{Recipe, RecipeParsingError};
 use colored::*;
 use console::{Emoji, Term};
-use dialoguer::{theme::ColorfulTheme, FuzzySelect, Input, Select};
+use dialoguer::{theme::ColorfulTheme, Confirm, FuzzySelect, Input, Select};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use std::fs;
-use std::io::{self};
+use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use tabled::{Style, Table, Tabled};
 
+/// Whether output is machine-readable JSON, see `set_json_output`
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Switches output to machine-readable JSON, suppressing screen clearing, tables, and color
+pub fn set_json_output(json: bool) {
+    JSON_OUTPUT.store(json, Ordering::Relaxed);
+    if json {
+        colored::control::set_override(false);
+    }
+}
+
+/// Returns true if output should be machine-readable JSON
+pub fn json_output() -> bool {
+    JSON_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// Prints any serializable object as JSON to stdout
+pub fn print_json<T: Serialize + ?Sized>(value: &T) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    serde_json::to_writer_pretty(&mut stdout, value)?;
+    writeln!(stdout)
+}
+
+/// File written by a subcommand, printed as JSON in place of a status message
+#[derive(Debug, Serialize)]
+pub struct Saved {
+    pub path: String,
+}
+
+/// Reports a written file, as `{"path": ...}` when output is JSON
+pub fn report_saved(what: &str, path: &Path) -> io::Result<()> {
+    match json_output() {
+        true => print_json(&Saved {
+            path: path.to_string_lossy().to_string(),
+        }),
+        false => {
+            notify_saved(what, path);
+            Ok(())
+        }
+    }
+}
+
+/// Notes a written file with `notify`, for subcommands printing the saved object as JSON
+pub fn notify_saved(what: &str, path: &Path) {
+    notify(&format!("{what} saved to {}", path.display()));
+}
+
+/// Prints a status message, sent to stderr when output is JSON to keep stdout parseable
+pub fn notify(msg: &str) {
+    if json_output() {
+        eprintln!("{msg}");
+    } else {
+        println!("{msg}");
+    }
+}
+
 /// Displays tool title after clearing terminal
 pub fn title(msg: &str) {
+    if json_output() {
+        return;
+    }
     let term = Term::stdout();
     term.clear_screen().unwrap();
     println!(
-        "{} {}v{}{}{}{} {} {} {} {}{}\n{}",
+        "{} \u{0104}v\u{0119}\u{0155}\u{015B}\u{0113} {} {} {} {}{}\n{}",
         "\u{222E}".purple(),
-        "\u{0104}",
-        "\u{0119}",
-        "\u{0155}",
-        "\u{015B}",
-        "\u{0113}",
         "\u{2563}".purple(),
         "A Meal Planner".cyan(),
         "\u{2560}".purple(),
@@ -44,10 +102,21 @@ pub fn get_user_input() -> io::Result<String> {
 
 /// Prints a table from a collection of objects which implement the `Tabled` trait
 pub fn print_table<T: Tabled>(rows: &Vec<T>) {
+    if json_output() {
+        return;
+    }
     let table = Table::new(rows).with(Style::psql()).to_string();
     println!("{table}");
 }
 
+/// Rejects recipe and template names that would be written outside their directory
+pub fn check_name(name: &str) -> Result<(), RecipeParsingError> {
+    match name.contains(['/', '\\']) || name.contains("..") {
+        true => Err(RecipeParsingError::InvalidName(name.into())),
+        false => Ok(()),
+    }
+}
+
 /// Fetches output path for recipe
 pub fn get_recipe_out_path(recipe_dir: &str, name: &str) -> PathBuf {
     assert!(!recipe_dir.trim().is_empty());
@@ -56,33 +125,90 @@ pub fn get_recipe_out_path(recipe_dir: &str, name: &str) -> PathBuf {
     out_path
 }
 
+/// Fetches path for a plan given its name (date)
+pub fn get_plan_path(plan_dir: &str, name: &str) -> PathBuf {
+    Path::new(&plan_dir).join(name).with_extension("yaml")
+}
+
 /// Fetches all recipes
-pub fn get_jsons(dir: &Path) -> io::Result<Vec<PathBuf>> {
-    fs::read_dir(dir)?
-        .into_iter()
-        .map(|x| x.map(|entry| entry.path()))
-        .collect()
+pub fn get_jsons(dir: &Path) -> Result<Vec<PathBuf>, RecipeParsingError> {
+    fs::read_dir(dir)
+        .and_then(|entries| entries.map(|x| x.map(|entry| entry.path())).collect())
+        .map_err(|e| file_error(dir, e))
+}
+
+/// Reads a file to a string, or stdin if `path` is `-`
+pub fn read_input(path: &Path) -> Result<String, RecipeParsingError> {
+    if path == Path::new("-") {
+        let mut buf = String::new();
+        io::stdin().read_to_string(&mut buf)?;
+        return Ok(buf);
+    }
+    fs::read_to_string(path).map_err(|e| file_error(path, e))
+}
+
+/// Reads and deserializes a YAML file, or stdin if `path` is `-`
+pub fn read_yaml<T: DeserializeOwned>(path: &Path) -> Result<T, RecipeParsingError> {
+    serde_yaml::from_str(&read_input(path)?).map_err(|e| match path == Path::new("-") {
+        true => RecipeParsingError::InvalidFile("<stdin>".into(), e),
+        false => RecipeParsingError::InvalidFile(path.into(), e),
+    })
+}
+
+/// Writes a string to a file
+pub fn write_file(path: &Path, contents: &str) -> Result<(), RecipeParsingError> {
+    fs::write(path, contents).map_err(|e| RecipeParsingError::FileError(path.into(), e))
+}
+
+/// Attaches the offending path to an I/O error
+fn file_error(path: &Path, e: io::Error) -> RecipeParsingError {
+    match e.kind() {
+        io::ErrorKind::NotFound => RecipeParsingError::NotFound(path.into()),
+        _ => RecipeParsingError::FileError(path.into(), e),
+    }
+}
+
+/// Error returned when a prompt is dismissed with Esc
+fn cancelled() -> io::Error {
+    io::Error::new(io::ErrorKind::Interrupted, "cancelled")
 }
 
 /// Generates a set of summaries for all recipes in a directory
-pub fn summarize_recipes(recipe_dir: &String) -> Result<Vec<String>, RecipeParsingError> {
+pub fn summarize_recipes(recipe_dir: &str) -> Result<Vec<String>, RecipeParsingError> {
     get_jsons(Path::new(&recipe_dir))?
         .iter()
         .map(|x| Ok(Recipe::try_from(x)?.summary()))
         .collect()
 }
 
+/// Loads all recipes in a directory
+pub fn load_recipes(recipe_dir: &str) -> Result<Vec<Recipe>, RecipeParsingError> {
+    get_jsons(Path::new(&recipe_dir))?
+        .iter()
+        .map(Recipe::try_from)
+        .collect()
+}
+
+/// Loads all recipes in a directory matching a query
+pub fn search_recipes(recipe_dir: &str, query: &Query) -> Result<Vec<Recipe>, RecipeParsingError> {
+    Ok(load_recipes(recipe_dir)?
+        .into_iter()
+        .filter(|x| query.matches(x))
+        .collect())
+}
+
 /// Converts summary back to recipe name
 pub fn recipe_name_from_summary(s: &str) -> Option<String> {
     s.split("--").next()?.trim().replace(" ", "-").into()
 }
 
-/// Wrapper for dialoguer::FuzzySelect
+/// Wrapper for dialoguer::FuzzySelect, Esc cancels
 pub fn fuzzy_select<T: std::fmt::Display>(items: &[T]) -> io::Result<usize> {
     FuzzySelect::with_theme(&ColorfulTheme::default())
         .items(items)
         .default(0)
-        .interact()
+        .interact_opt()?
+        .ok_or_else(cancelled)
 }
 
 /// Wrapper for dialoguer::Input
@@ -93,10 +219,19 @@ pub fn input_msg(msg: &str) -> io::Result<String> {
         .interact_text()
 }
 
-/// Wrapper for dialoguer::Select
+/// Wrapper for dialoguer::Confirm, Esc cancels
+pub fn confirm(msg: &str) -> io::Result<bool> {
+    Confirm::new()
+        .with_prompt(msg)
+        .interact_opt()?
+        .ok_or_else(cancelled)
+}
+
+/// Wrapper for dialoguer::Select, Esc or q cancels
 pub fn select<T: std::fmt::Display>(items: &[T]) -> io::Result<usize> {
     Select::with_theme(&ColorfulTheme::default())
         .items(items)
         .default(0)
-        .interact()
+        .interact_opt()?
+        .ok_or_else(cancelled)
 }
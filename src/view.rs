@@ -1,17 +1,30 @@
 //! Module for viewing recipes using `fuzzy` search
 use crate::errors::RecipeParsingError;
-use crate::utils::{get_jsons, input_msg, summarize_recipes, title};
-use crate::Recipe;
+use crate::query::Query;
+use crate::utils::{input_msg, json_output, notify, print_json, search_recipes, title};
+use crate::{Recipe, RecipeRow};
 use dialoguer::{theme, FuzzySelect};
-use std::path::Path;
 
-/// Logic for displaying recipes
-pub fn display_recipes(recipe_dir: &String) -> Result<(), RecipeParsingError> {
+/// Logic for displaying recipes, narrowed down by `query`
+pub fn display_recipes(recipe_dir: &str, query: &Query) -> Result<(), RecipeParsingError> {
     let base = "\t\u{21F8} View Recipes\n\n";
-    let mainscr = format!("{base}Type to search recipes then hit ENTER\n\n");
-    let recipe_paths = get_jsons(Path::new(&recipe_dir))?;
-    let recipe_summaries = summarize_recipes(&recipe_dir)?;
-    let mut recipe: Option<Recipe> = None;
+    let mainscr = format!("{base}Type to search recipes then hit ENTER, or ESC to quit\n\n");
+    let recipes = search_recipes(recipe_dir, query)?;
+    if json_output() {
+        let rows = recipes
+            .iter()
+            .enumerate()
+            .map(|(i, x)| x.clone().to_row(i))
+            .collect::<Vec<RecipeRow>>();
+        print_json(&rows)?;
+        return Ok(());
+    }
+    if recipes.is_empty() {
+        notify("No recipes match the query");
+        return Ok(());
+    }
+    let recipe_summaries = recipes.iter().map(|x| x.summary()).collect::<Vec<String>>();
+    let mut recipe: Option<&Recipe> = None;
     loop {
         title(&mainscr);
         if let Some(ref rec) = recipe {
@@ -22,8 +35,10 @@ pub fn display_recipes(recipe_dir: &String) -> Result<(), RecipeParsingError> {
         let select_idx = FuzzySelect::with_theme(&theme::ColorfulTheme::default())
             .items(&recipe_summaries)
             .default(0)
-            .interact()
-            .unwrap();
-        recipe = Recipe::try_from(&recipe_paths[select_idx]).ok();
+            .interact_opt()?;
+        match select_idx {
+            Some(idx) => recipe = recipes.get(idx),
+            None => return Ok(()),
+        }
     }
 }
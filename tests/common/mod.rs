@@ -0,0 +1,46 @@
+//! Helpers shared by the integration tests
+#![allow(dead_code)]
+
+use averse::plan::Meal;
+use chrono::NaiveDate;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub fn date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+pub fn cook(name: &str) -> Vec<Meal> {
+    vec![Meal::Cook(name.into())]
+}
+
+/// Directory under the system temp dir, unique to each test and removed when dropped
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNT.fetch_add(1, Ordering::Relaxed);
+        let path = env::temp_dir().join(format!("averse-test-{name}-{}-{n}", process::id()));
+        fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    /// Path of the directory as a `&str`, as taken by the subcommand logic
+    pub fn dir(&self) -> &str {
+        self.0.to_str().unwrap()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
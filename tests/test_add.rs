@@ -0,0 +1,156 @@
+mod common;
+
+use averse::add::{add_recipe_from_args, add_recipe_from_file};
+use averse::errors::{AddError, ErrorCategory, RecipeParsingError};
+use averse::utils::get_recipe_out_path;
+use averse::Recipe;
+use common::TempDir;
+use serde_json::{json, Value};
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn args(xs: &[&str]) -> Vec<String> {
+    xs.iter().map(|x| x.to_string()).collect()
+}
+
+/// Saved recipe as JSON, fields of `Recipe` are private to the crate
+fn load(recipe_dir: &str, name: &str) -> Value {
+    let recipe = Recipe::try_from(&get_recipe_out_path(recipe_dir, name)).unwrap();
+    serde_json::to_value(recipe).unwrap()
+}
+
+fn n_ingredients(recipe_dir: &str, name: &str) -> usize {
+    load(recipe_dir, name)["ingredients"]
+        .as_array()
+        .unwrap()
+        .len()
+}
+
+#[test]
+fn test_add_recipe_from_args() {
+    let dir = TempDir::new("add-args");
+    let add = |ingredients: &[&str], force: bool| {
+        add_recipe_from_args(
+            dir.dir(),
+            "rice bowl",
+            &args(&["weeknight"]),
+            &Some(2),
+            &args(ingredients),
+            &args(&["Cook rice"]),
+            &force,
+        )
+    };
+    add(&["1 cup rice", "2 item egg"], false).unwrap();
+    assert_eq!(n_ingredients(dir.dir(), "rice-bowl"), 2);
+    assert_eq!(load(dir.dir(), "rice-bowl")["servings"], 2);
+
+    assert!(matches!(
+        add(&["1 cup rice"], false),
+        Err(AddError::AlreadyExists(_))
+    ));
+    assert_eq!(n_ingredients(dir.dir(), "rice-bowl"), 2);
+    add(&["1 cup rice"], true).unwrap();
+    assert_eq!(n_ingredients(dir.dir(), "rice-bowl"), 1);
+
+    match add(&["one cup rice"], true) {
+        Err(e @ AddError::InvalidIngredient(..)) => {
+            assert_eq!(e.category(), ErrorCategory::Input);
+            assert!(e.to_string().contains("one cup rice"));
+        }
+        _ => panic!("Expected an invalid ingredient"),
+    }
+
+    let err = add_recipe_from_args(
+        dir.dir(),
+        "sub/../../escaped",
+        &[],
+        &None,
+        &args(&["1 cup water"]),
+        &[],
+        &false,
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        AddError::RecipeError(RecipeParsingError::InvalidName(_))
+    ));
+}
+
+#[test]
+fn test_add_recipe_from_file() {
+    let dir = TempDir::new("add-file");
+    let add = |yaml: &str| {
+        let path = dir.path().join("recipe.yaml");
+        fs::write(&path, yaml).unwrap();
+        add_recipe_from_file(dir.dir(), path.to_str().unwrap(), &true)
+    };
+    add("name: toast
+ingredients:
+  - 2 item bread
+  - name: butter
+    amount: 1
+    unit: tbsp
+steps: [Toast bread]
+")
+    .unwrap();
+    assert_eq!(
+        load(dir.dir(), "toast")["ingredients"][1],
+        json!({"name": "butter", "amount": 1.0, "unit": "Tbsp"})
+    );
+
+    let invalid = [
+        "  - name: ''\n    amount: 1\n    unit: tbsp",
+        "  - name: butter\n    amount: -1\n    unit: tbsp",
+        "  - name: butter\n    amount: 1\n    unit: bushel",
+        "  - name: butter\n    unit: tbsp",
+        "  - -1 tbsp butter",
+        "  - 1",
+    ];
+    for entry in invalid {
+        match add(&format!("name: toast\ningredients:\n{entry}\n")) {
+            Err(e @ (AddError::InvalidIngredient(..) | AddError::InvalidEntry(..))) => {
+                assert_eq!(e.category(), ErrorCategory::Input)
+            }
+            x => panic!("Expected an invalid ingredient for {entry:?}, got {x:?}"),
+        }
+    }
+    assert_eq!(n_ingredients(dir.dir(), "toast"), 2);
+
+    let recipe_dir = dir.path().join("recipes");
+    fs::create_dir(&recipe_dir).unwrap();
+    let path = dir.path().join("escape.yaml");
+    fs::write(&path, "name: ../escaped\ningredients: [1 cup water]\n").unwrap();
+    let err = add_recipe_from_file(recipe_dir.to_str().unwrap(), path.to_str().unwrap(), &true)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        AddError::RecipeError(RecipeParsingError::InvalidName(_))
+    ));
+    assert_eq!(err.category(), ErrorCategory::Input);
+    assert!(!dir.path().join("escaped.yaml").exists());
+}
+
+#[test]
+fn test_add_recipe_from_stdin() {
+    let dir = TempDir::new("add-stdin");
+    let add = || {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_averse"))
+            .args(["--recipe-dir", dir.dir(), "add", "--from-file", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(b"name: tea\ningredients: [1 cup water]\n")
+            .unwrap();
+        child.wait().unwrap()
+    };
+    assert!(add().success());
+    assert_eq!(n_ingredients(dir.dir(), "tea"), 1);
+    assert_eq!(add().code(), Some(ErrorCategory::Input.exit_code()));
+}
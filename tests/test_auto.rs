@@ -0,0 +1,121 @@
+mod common;
+
+use averse::auto::*;
+use averse::errors::PlanError;
+use averse::plan::Slot;
+use averse::utils::load_recipes;
+use averse::Ingredient;
+use chrono::NaiveDate;
+use common::{cook, date};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::{HashMap, HashSet};
+
+fn week() -> Vec<NaiveDate> {
+    date("2022-08-07").iter_days().take(7).collect()
+}
+
+#[test]
+fn test_requirement() {
+    let fri = date("2022-08-12");
+    let req: Requirement = "fri:dinner=tag:pizza".parse().unwrap();
+    assert!(req.applies(fri, Slot::Dinner));
+    assert!(!req.applies(fri, Slot::Lunch));
+    assert!(!req.applies(date("2022-08-11"), Slot::Dinner));
+
+    let req: Requirement = "Breakfast=tag:breakfast".parse().unwrap();
+    assert!(req.applies(fri, Slot::Breakfast));
+    let req: Requirement = "2022-08-12=-tag:dessert".parse().unwrap();
+    assert!(req.applies(fri, Slot::Snack));
+
+    assert!(matches!(
+        "someday=tag:pizza".parse::<Requirement>(),
+        Err(PlanError::InvalidRule(_))
+    ));
+    assert!(matches!(
+        "fri:brunch=tag:pizza".parse::<Requirement>(),
+        Err(PlanError::InvalidSlot(_))
+    ));
+}
+
+#[test]
+fn test_fill() {
+    let recipes = load_recipes("./recipes").unwrap();
+    let rules = Rules {
+        slots: vec![Slot::Breakfast, Slot::Dinner],
+        require: vec![
+            "breakfast=tag:breakfast".into(),
+            "dinner=-tag:breakfast".into(),
+            "fri:dinner=tag:pizza".into(),
+        ],
+        max_repeats: 7,
+        ..Default::default()
+    };
+    let recent = HashSet::from(["chili".to_string()]);
+    let generator = Generator::new(recipes.clone(), &recent, &rules).unwrap();
+    let mut rng = StdRng::seed_from_u64(42);
+    let plan = generator.fill(&mut rng, &week()).unwrap();
+    assert_eq!(plan.len(), 7);
+    for meals in plan.values() {
+        assert_eq!(meals[&Slot::Breakfast], cook("bagel-breakfast"));
+        assert_ne!(meals[&Slot::Dinner], cook("chili"));
+    }
+    assert_eq!(
+        plan[&date("2022-08-12")][&Slot::Dinner],
+        cook("cast-iron-pizza")
+    );
+
+    // Swapping a day keeps the rest of the plan
+    let swapped = generator.swap(&mut rng, date("2022-08-08"), &plan).unwrap();
+    assert_eq!(swapped[&date("2022-08-09")], plan[&date("2022-08-09")]);
+    assert_ne!(
+        swapped[&date("2022-08-08")][&Slot::Dinner],
+        plan[&date("2022-08-08")][&Slot::Dinner]
+    );
+
+    // Only one breakfast recipe exists, so it can't fill a week without repeats
+    let rules = Rules {
+        max_repeats: 1,
+        ..rules
+    };
+    let generator = Generator::new(recipes, &recent, &rules).unwrap();
+    assert!(matches!(
+        generator.fill(&mut rng, &week()),
+        Err(PlanError::Unsatisfiable(_, Slot::Breakfast))
+    ));
+}
+
+#[test]
+fn test_max_groceries() {
+    let recipes = load_recipes("./recipes").unwrap();
+    let rules = Rules {
+        max_groceries: Some(20),
+        ..Default::default()
+    };
+    let generator = Generator::new(recipes.clone(), &HashSet::new(), &rules).unwrap();
+    let mut rng = StdRng::seed_from_u64(7);
+    let plan = generator.fill(&mut rng, &week()[..3]).unwrap();
+    let ingredients = recipes
+        .iter()
+        .map(|x| {
+            (
+                x.slug(),
+                serde_json::to_value(x).unwrap()["ingredients"].clone(),
+            )
+        })
+        .collect::<HashMap<String, serde_json::Value>>();
+    let groceries = plan
+        .values()
+        .flat_map(|x| x[&Slot::Dinner].iter())
+        .flat_map(|x| ingredients[x.recipe()].as_array().unwrap().clone())
+        .map(|x| serde_json::from_value::<Ingredient>(x).unwrap().key())
+        .collect::<HashSet<String>>();
+    assert!(groceries.len() <= 20);
+
+    let rules = Rules {
+        max_groceries: Some(1),
+        ..Default::default()
+    };
+    let generator = Generator::new(recipes, &HashSet::new(), &rules).unwrap();
+    assert!(generator.fill(&mut rng, &week()).is_err());
+}
@@ -0,0 +1,71 @@
+use averse::category::{Categories, Category};
+
+#[test]
+fn test_category_guess() {
+    assert_eq!(Category::guess("ground beef"), Category::Meat);
+    assert_eq!(Category::guess("Yellow Onion"), Category::Produce);
+    assert_eq!(Category::guess("onion powder"), Category::Spices);
+    assert_eq!(Category::guess("chicken stock"), Category::Pantry);
+    assert_eq!(Category::guess("cheddar cheese"), Category::Dairy);
+    assert_eq!(Category::guess("pizza dough"), Category::Other);
+    assert_eq!(Category::guess("unsalted butter"), Category::Dairy);
+    assert_eq!(Category::guess("eggplant"), Category::Produce);
+    assert_eq!(Category::guess("graham crackers"), Category::Pantry);
+    assert_eq!(Category::guess("large eggs"), Category::Dairy);
+    assert_eq!(Category::guess("Roma tomatoes"), Category::Produce);
+    assert_eq!(Category::guess("blueberries"), Category::Produce);
+    assert_eq!(Category::guess("bay leaves"), Category::Spices);
+    assert_eq!(Category::guess("red pepper flakes"), Category::Spices);
+    assert_eq!(Category::guess("vanilla ice-cream"), Category::Frozen);
+}
+
+#[test]
+fn test_categories() {
+    let categories: Categories = serde_yaml::from_str(
+        "
+        store: corner-market
+        stores:
+          corner-market: [spices, dairy]
+          warehouse: [frozen]
+        ingredients:
+          Pizza Dough: frozen
+        ",
+    )
+    .unwrap();
+    assert_eq!(categories.category("pizza dough"), Category::Frozen);
+    assert_eq!(categories.category("ground beef"), Category::Meat);
+    assert_eq!(
+        categories.aisles()[..3],
+        [Category::Spices, Category::Dairy, Category::Produce]
+    );
+    assert_eq!(categories.aisles().len(), Category::ALL.len());
+
+    let groceries = [
+        "1 lb ground beef",
+        "1 tbsp cumin",
+        "2 item yellow onion",
+        "1 item feta",
+    ]
+    .iter()
+    .map(|x| x.parse().unwrap())
+    .collect::<Vec<averse::Ingredient>>();
+    let order = categories
+        .group(&groceries)
+        .iter()
+        .map(|(category, _)| *category)
+        .collect::<Vec<Category>>();
+    assert_eq!(
+        order,
+        vec![
+            Category::Spices,
+            Category::Dairy,
+            Category::Produce,
+            Category::Meat
+        ]
+    );
+
+    let missing = serde_yaml::from_str::<Categories>("store: costco\nstores: {warehouse: [meat]}");
+    assert!(missing.is_err());
+    let ambiguous = serde_yaml::from_str::<Categories>("stores: {a: [meat], b: [dairy]}");
+    assert!(ambiguous.is_err());
+}
@@ -0,0 +1,27 @@
+use averse::completions::{with_dynamic_values, DynamicValues};
+use clap::{Arg, Command};
+
+#[test]
+fn test_with_dynamic_values() {
+    let values = DynamicValues::load("./recipes", "./plans", "./templates").unwrap();
+    assert!(values.recipes.contains(&"chili"));
+    assert!(values.plans.contains(&"2022-07-15"));
+    assert!(values.templates.contains(&"weeknight-rotation"));
+    assert!(values.tags.contains(&"breakfast"));
+
+    let cmd = Command::new("averse")
+        .subcommand(Command::new("show").arg(Arg::new("recipe").takes_value(true)))
+        .subcommand(Command::new("view").arg(Arg::new("query").takes_value(true)));
+    let cmd = with_dynamic_values(cmd, &values);
+    let possible = |sub: &str, arg: &str| {
+        cmd.find_subcommand(sub)
+            .unwrap()
+            .get_arguments()
+            .find(|x| x.get_id() == arg)
+            .unwrap()
+            .get_possible_values()
+            .map_or(0, |x| x.len())
+    };
+    assert_eq!(possible("show", "recipe"), values.recipes.len());
+    assert_eq!(possible("view", "query"), 0);
+}
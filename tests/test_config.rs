@@ -0,0 +1,37 @@
+mod common;
+
+use averse::config::*;
+use chrono::Weekday;
+use common::TempDir;
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn test_find_config() {
+    let dir = TempDir::new("config");
+    let root = dir.path();
+    let nested = root.join("recipes").join("nested");
+    fs::create_dir_all(&nested).unwrap();
+    fs::write(
+        root.join(CONFIG_FILE),
+        "recipe-dir = \"recipes\"\nplan-dir = \"/tmp/plans\"\nweek-start = \"mon\"\nn-plans = 3\npantry-file = \"stock.yaml\"\ncategory-file = \"/tmp/aisles.yaml\"\n",
+    )
+    .unwrap();
+
+    let path = find_config(&nested).unwrap();
+    assert_eq!(path, root.join(CONFIG_FILE));
+    let config = Config::from_path(&path).unwrap();
+    assert_eq!(config.recipe_dir, root.join("recipes").to_str().unwrap());
+    assert_eq!(config.plan_dir, "/tmp/plans");
+    assert_eq!(config.week_start, Weekday::Mon);
+    assert_eq!(config.n_plans, 3);
+    assert_eq!(config.units, UnitSystem::Imperial);
+    assert_eq!(config.household().pantry_file, root.join("stock.yaml"));
+    assert_eq!(
+        config.household().category_file,
+        Path::new("/tmp/aisles.yaml")
+    );
+
+    fs::write(root.join(CONFIG_FILE), "week-start = \"someday\"\n").unwrap();
+    assert!(Config::from_path(&path).is_err());
+}
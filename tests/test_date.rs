@@ -0,0 +1,25 @@
+use averse::date::*;
+use chrono::{NaiveDate, Weekday};
+
+#[test]
+fn test_parse_date() {
+    // Friday
+    let today = NaiveDate::from_ymd_opt(2022, 7, 15).unwrap();
+    let day = |d| NaiveDate::from_ymd_opt(2022, 7, d).unwrap();
+    assert_eq!(parse_date("2022-07-31", today).unwrap(), day(31));
+    assert_eq!(parse_date("today", today).unwrap(), day(15));
+    assert_eq!(parse_date("Sunday", today).unwrap(), day(17));
+    assert_eq!(parse_date("fri", today).unwrap(), day(15));
+    assert_eq!(parse_date("next-friday", today).unwrap(), day(22));
+    assert_eq!(parse_date("+1w", today).unwrap(), day(22));
+    assert_eq!(parse_date("-3d", today).unwrap(), day(12));
+    assert!(parse_date("2022-15-22", today).is_err());
+    assert!(parse_date("+1y", today).is_err());
+    assert!(parse_date("next-someday", today).is_err());
+    assert!(parse_date("+999999999d", today).is_err());
+    assert!(parse_date("+9999999999999999w", today).is_err());
+    assert!(parse_date("-9999999999999999w", today).is_err());
+
+    assert_eq!(upcoming_week(Weekday::Sun, today), day(17));
+    assert_eq!(upcoming_week(Weekday::Fri, today), day(15));
+}
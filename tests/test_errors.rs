@@ -0,0 +1,24 @@
+use averse::errors::{ErrorCategory, PlanError};
+use averse::plan::Plan;
+use averse::show::resolve_recipe;
+use chrono::NaiveDate;
+
+#[test]
+fn test_error_category() {
+    let err = Plan::load(
+        &NaiveDate::from_ymd_opt(1999, 1, 1).unwrap(),
+        "./recipes",
+        "./plans",
+    )
+    .unwrap_err();
+    assert_eq!(err.category(), ErrorCategory::NotFound);
+    assert!(err.to_string().contains("1999-01-01.yaml"));
+
+    let err = resolve_recipe("./not-a-dir", "chili").unwrap_err();
+    assert_eq!(err.category().exit_code(), 4);
+
+    assert_eq!(
+        PlanError::InvalidDay("x".into()).category(),
+        ErrorCategory::Input
+    );
+}
@@ -0,0 +1,15 @@
+use averse::export::*;
+use averse::plan::Plan;
+use averse::utils::get_plan_path;
+
+#[test]
+fn test_plan_to_ics() {
+    let plan = Plan::try_from(&get_plan_path("./plans", "2022-07-24")).unwrap();
+    let ics = plan_to_ics(&plan, "./recipes").unwrap();
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+    assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+    assert!(ics.contains("DTSTART;VALUE=DATE:20220724"));
+    assert!(ics.contains("DTSTART;VALUE=DATE:20220725"));
+    assert!(ics.split("\r\n").all(|line| line.len() <= 75));
+}
@@ -0,0 +1,31 @@
+mod common;
+
+use averse::groceries::*;
+use chrono::NaiveDate;
+use common::date;
+
+#[test]
+fn test_combine_groceries() {
+    let (from, to) = (Some(date("2022-07-20")), Some(date("2022-08-07")));
+    let mut plans = select_plans("./recipes", "./plans", &[], &from, &to).unwrap();
+    let dates = plans.iter().map(|x| x.date).collect::<Vec<NaiveDate>>();
+    assert_eq!(dates, vec![date("2022-07-24"), date("2022-07-31")]);
+    let by_date = select_plans("./recipes", "./plans", &dates, &None, &None).unwrap();
+    assert_eq!(by_date.len(), 2);
+
+    let separate = plans
+        .iter_mut()
+        .map(|x| x.compile_groceries().unwrap().groceries().len())
+        .sum::<usize>();
+    let combined = combine_groceries(&mut plans).unwrap();
+    assert!(combined.len() < separate);
+    let (garlic, sources) = combined
+        .iter()
+        .find(|(x, _)| x.to_string().ends_with(" Item garlic"))
+        .unwrap();
+    assert_eq!(garlic.to_string(), "4 Item garlic");
+    assert_eq!(
+        sources.iter().collect::<Vec<_>>(),
+        dates.iter().collect::<Vec<_>>()
+    );
+}
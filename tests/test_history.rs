@@ -0,0 +1,40 @@
+mod common;
+
+use averse::history::*;
+use averse::plan::{last_planned, Plan};
+use common::date;
+
+#[test]
+fn test_recipe_history() {
+    let plans: Vec<Plan> = [
+        "date: 2022-08-01\nrecipes:\n  monday: [chili]\n  tuesday: [leftovers: chili]\n",
+        "date: 2022-08-08\nrecipes:\n  monday: [chili, chimichurri]\n  friday: [chili]\n",
+    ]
+    .iter()
+    .map(|x| serde_yaml::from_str(x).unwrap())
+    .collect();
+    let history = recipe_history(&plans);
+    let last = last_planned(&plans[..1]);
+    assert_eq!(last["chili"], date("2022-08-01"));
+    assert_eq!(
+        history[0],
+        RecipeHistory {
+            recipe: "chili".into(),
+            planned: 3,
+            last: date("2022-08-12"),
+            average_gap: Some(5.5),
+        }
+    );
+    assert_eq!(history[1].recipe, "chimichurri");
+    assert_eq!(history[1].average_gap, None);
+
+    let forgotten_at = |today| {
+        forgotten(&history, date(today), 7)
+            .iter()
+            .map(|x| x.recipe.as_str())
+            .collect::<Vec<&str>>()
+    };
+    assert!(forgotten_at("2022-08-14").is_empty());
+    assert_eq!(forgotten_at("2022-08-16"), vec!["chimichurri"]);
+    assert_eq!(forgotten_at("2022-09-01"), vec!["chili", "chimichurri"]);
+}
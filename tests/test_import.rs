@@ -0,0 +1,28 @@
+use averse::import::parse_csv;
+
+const CSV: &str = "name,tags,amount,unit,ingredient,step
+tacos,mexican; weeknight,1,lb,ground beef,Brown beef
+tacos,weeknight,8,item,tortillas,Warm tortillas
+soup,soup,2,cup,stock,Boil
+soup,,1,bushel,carrot,
+,,1,cup,rice,
+../escape,,1,cup,rice,
+soup,,-2,cup,water,
+soup,,NaN,cup,water,
+";
+
+#[test]
+fn test_parse_csv() {
+    let (recipes, errors) = parse_csv(CSV.as_bytes());
+    assert_eq!(recipes.len(), 2);
+    assert_eq!(recipes[0].0, 2);
+    assert!(recipes[0].1.summary().ends_with("-- mexican, weeknight"));
+
+    let lines = errors.iter().map(|e| e.line).collect::<Vec<u64>>();
+    assert_eq!(lines, vec![5, 6, 7, 8, 9]);
+    assert_eq!(errors[0].recipe, "soup");
+    assert!(errors[0].error.contains("bushel"));
+    assert_eq!(errors[2].recipe, "../escape");
+    assert!(errors[3].error.contains("-2"));
+    assert!(errors[4].error.contains("NaN"));
+}
@@ -0,0 +1,22 @@
+use averse::Ingredient;
+use std::str::FromStr;
+
+#[test]
+fn test_ingredient_from_str() {
+    let ingr = Ingredient::from_str("1.5  lb ground beef").unwrap();
+    assert_eq!(ingr.to_string(), "1.5 Lb ground beef");
+    assert!(Ingredient::from_str("").is_err());
+    assert!(Ingredient::from_str("1").is_err());
+    assert!(Ingredient::from_str("1 lb").is_err());
+    assert!(Ingredient::from_str("one lb beef").is_err());
+    assert!(Ingredient::from_str("1 bushel beef").is_err());
+    assert!(Ingredient::from_str("-1 lb beef").is_err());
+    assert!(Ingredient::from_str("NaN lb beef").is_err());
+}
+
+#[test]
+fn test_ingredient_key() {
+    let key = |s: &str| Ingredient::from_str(s).unwrap().key();
+    assert_eq!(key("1 item Garlic"), key("2 item garlic"));
+    assert_ne!(key("1 item garlic"), key("1 tbsp garlic"));
+}
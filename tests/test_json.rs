@@ -0,0 +1,89 @@
+use averse::add::add_recipe;
+use averse::category::Categories;
+use averse::config::{Household, UnitSystem};
+use averse::errors::{AddError, ErrorCategory, PlanError};
+use averse::pantry::Pantry;
+use averse::plan::{plan_week, Plan};
+use averse::query::Query;
+use averse::show::resolve_recipe;
+use averse::utils::set_json_output;
+use averse::GroceryRow;
+use chrono::NaiveDate;
+use serde::Serialize;
+use serde_json::Value;
+use tabled::Tabled;
+
+/// Keys of a JSON object, sorted
+fn keys<T: Serialize>(value: &T) -> Vec<String> {
+    match serde_json::to_value(value).unwrap() {
+        Value::Object(map) => map.keys().cloned().collect(),
+        x => panic!("Expected an object, got {x}"),
+    }
+}
+
+fn plan() -> Plan {
+    let date = NaiveDate::from_ymd_opt(2022, 7, 31).unwrap();
+    Plan::load(&date, "./recipes", "./plans").unwrap()
+}
+
+#[test]
+fn test_json_shapes() {
+    // list
+    let recipe = resolve_recipe("./recipes", "chili").unwrap();
+    assert_eq!(
+        keys(&recipe.clone().to_row(0)),
+        vec!["ID", "Ingredients", "LastPlanned", "Name", "Tags"]
+    );
+
+    // show
+    assert_eq!(
+        keys(&recipe),
+        vec!["ingredients", "name", "servings", "steps", "tags"]
+    );
+    let ingredients = serde_json::to_value(&recipe).unwrap()["ingredients"].clone();
+    assert_eq!(keys(&ingredients[0]), vec!["amount", "name", "unit"]);
+
+    // behold
+    let mut plan = plan();
+    assert_eq!(keys(&plan), vec!["date", "end", "recipes"]);
+
+    // plan grocery list
+    plan.compile_groceries().unwrap();
+    let rows = plan.get_grocery_table(&Pantry::default(), &Categories::default());
+    assert_eq!(
+        keys(&rows[0]),
+        vec![
+            "Amount",
+            "Buy",
+            "Category",
+            "Have",
+            "Id",
+            "Ingredient",
+            "Unit"
+        ]
+    );
+    // Terminal tables read Need/Have/Buy, JSON keeps Amount
+    assert_eq!(GroceryRow::headers()[2..5], ["Need", "Have", "Buy"]);
+}
+
+#[test]
+fn test_json_rejects_prompts() {
+    set_json_output(true);
+    let err = add_recipe("./recipes", &UnitSystem::Imperial, &false).unwrap_err();
+    assert!(matches!(err, AddError::NoPrompt(_)));
+    assert_eq!(err.category(), ErrorCategory::Input);
+    assert!(err.to_string().contains("--from-file"));
+
+    let date = NaiveDate::from_ymd_opt(2030, 1, 6).unwrap();
+    let err = plan_week(
+        "./recipes",
+        "./plans",
+        &date,
+        &7,
+        &Query::default(),
+        &Household::default(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, PlanError::NoPrompt(_)));
+    assert_eq!(err.category(), ErrorCategory::Input);
+}
@@ -0,0 +1,31 @@
+use averse::list::{filter_query, list_recipes, SortBy};
+use averse::query::Query;
+use averse::utils::get_recipe_out_path;
+use averse::Recipe;
+
+#[test]
+fn test_filter_query() {
+    let chili = Recipe::try_from(&get_recipe_out_path("./recipes", "chili")).unwrap();
+    let s = |x: &[&str]| x.iter().map(|x| x.to_string()).collect::<Vec<String>>();
+    let matches = |query: &str, tags: &[&str], without_tags: &[&str], ingredients: &[&str]| {
+        query
+            .parse::<Query>()
+            .unwrap()
+            .and(filter_query(&s(tags), &s(without_tags), &s(ingredients)))
+            .matches(&chili)
+    };
+    assert!(matches("", &[], &[], &[]));
+    assert!(matches("", &["Instant Pot"], &[], &["beef"]));
+    assert!(!matches("", &["dessert"], &[], &[]));
+    assert!(!matches("", &[], &["chili"], &[]));
+    assert!(!matches("", &[], &[], &["beef", "tofu"]));
+    assert!(matches("name:chili", &[], &[], &["BEEF"]));
+    assert!(!matches("name:soup", &[], &[], &["beef"]));
+}
+
+#[test]
+fn test_list_without_plans() {
+    for sort in [SortBy::Name, SortBy::LastPlanned] {
+        list_recipes("./recipes", "./no-such-plans", &"".parse().unwrap(), &sort).unwrap();
+    }
+}
@@ -0,0 +1,28 @@
+use averse::orf::*;
+use averse::utils::get_recipe_out_path;
+use averse::{Recipe, Unit};
+
+#[test]
+fn test_orf_roundtrip() {
+    let recipe = Recipe::try_from(&get_recipe_out_path("./recipes", "chili")).unwrap();
+    let orf = OrfRecipe::from(&recipe);
+    assert_eq!(orf.recipe_name, "chili");
+    assert_eq!(orf.notes, vec!["Tags: instant pot, chili, cold weather"]);
+
+    let yaml = serde_yaml::to_string(&orf).unwrap();
+    let parsed: OrfRecipe = serde_yaml::from_str(&yaml).unwrap();
+    let roundtrip = Recipe::try_from(parsed).unwrap();
+    assert_eq!(
+        serde_yaml::to_string(&recipe).unwrap(),
+        serde_yaml::to_string(&roundtrip).unwrap()
+    );
+}
+
+#[test]
+fn test_unit_from_orf() {
+    assert!(matches!(unit_from_orf("cups").unwrap(), Unit::Cup));
+    assert!(matches!(unit_from_orf("Tablespoons").unwrap(), Unit::Tbsp));
+    assert!(matches!(unit_from_orf("g").unwrap(), Unit::Gram));
+    assert!(matches!(unit_from_orf("").unwrap(), Unit::Item));
+    assert!(unit_from_orf("bushel").is_err());
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use averse::category::Categories;
+use averse::config::Household;
+use averse::errors::{ErrorCategory, PantryError};
+use averse::pantry::*;
+use averse::plan::{mark_cooked, plan_week_from_meals, Plan};
+use averse::Ingredient;
+use chrono::NaiveDate;
+use common::{date, TempDir};
+
+fn ingr(s: &str) -> Ingredient {
+    s.parse().unwrap()
+}
+
+fn args(xs: &[&str]) -> Vec<String> {
+    xs.iter().map(|x| x.to_string()).collect()
+}
+
+#[test]
+fn test_pantry() {
+    let mut pantry = Pantry::default();
+    pantry.add(ingr("2 can kidney beans"));
+    pantry.add(ingr("1 Can Kidney Beans"));
+    pantry.add(ingr("1 lb ground beef"));
+    assert_eq!(pantry.have(&ingr("1 can kidney beans")), 3.0);
+    assert_eq!(pantry.have(&ingr("1 gram kidney beans")), 0.0);
+
+    assert_eq!(pantry.remove(&ingr("5 can kidney beans")), 3.0);
+    assert_eq!(pantry.items().len(), 1);
+    assert!(pantry.remove_all("Ground Beef"));
+    assert!(!pantry.remove_all("ground beef"));
+
+    pantry.add(ingr("2 can kidney beans"));
+    pantry.add(ingr("2 tbsp cumin seed"));
+    pantry.deduct(&[ingr("1 can kidney beans"), ingr("4 tbsp cumin seed")]);
+    let items = pantry
+        .items()
+        .iter()
+        .map(|x| x.to_string())
+        .collect::<Vec<String>>();
+    assert_eq!(items, vec!["1 Can kidney beans"]);
+}
+
+#[test]
+fn test_grocery_table() {
+    let mut plan = Plan::load(
+        &NaiveDate::from_ymd_opt(2022, 7, 31).unwrap(),
+        "./recipes",
+        "./plans",
+    )
+    .unwrap();
+    plan.compile_groceries().unwrap();
+    let name = plan.groceries()[0].to_string();
+    let mut pantry = Pantry::default();
+    pantry.add(ingr(&name));
+    pantry.add(ingr(&name));
+    let rows = plan.get_grocery_table(&pantry, &Categories::default());
+    let row = rows.iter().find(|x| name.ends_with(&x.Ingredient)).unwrap();
+    assert_eq!(row.Have, row.Amount * 2.0);
+    assert_eq!(row.Buy, 0.0);
+    assert!(rows
+        .iter()
+        .filter(|x| x.Have == 0.0)
+        .all(|x| x.Buy == x.Amount));
+
+    assert!(!serde_yaml::to_string(&plan).unwrap().contains("cooked"));
+    plan.cooked = true;
+    let yaml = serde_yaml::to_string(&plan).unwrap();
+    assert!(serde_yaml::from_str::<Plan>(&yaml).unwrap().cooked);
+}
+
+#[test]
+fn test_pantry_file() {
+    let dir = TempDir::new("pantry");
+    let path = dir.path().join("pantry.yaml");
+    add_to_pantry(&path, &args(&["2 can kidney beans", "1 lb ground beef"])).unwrap();
+    add_to_pantry(&path, &args(&["1 can kidney beans"])).unwrap();
+    assert_eq!(Pantry::load(&path).unwrap().items().len(), 2);
+
+    remove_from_pantry(&path, &args(&["Ground Beef"])).unwrap();
+    remove_from_pantry(&path, &args(&["1 can kidney beans"])).unwrap();
+    let items = Pantry::load(&path)
+        .unwrap()
+        .items()
+        .iter()
+        .map(|x| x.to_string())
+        .collect::<Vec<String>>();
+    assert_eq!(items, vec!["2 Can kidney beans"]);
+
+    match remove_from_pantry(&path, &args(&["ground beef"])) {
+        Err(e @ PantryError::NotInPantry(_)) => assert_eq!(e.category(), ErrorCategory::NotFound),
+        x => panic!("Expected ground beef to be missing, got {x:?}"),
+    }
+    assert!(matches!(
+        add_to_pantry(&path, &args(&["two cans beans"])),
+        Err(PantryError::InvalidIngredient(..))
+    ));
+    assert_eq!(Pantry::load(&path).unwrap().items().len(), 1);
+}
+
+#[test]
+fn test_mark_cooked() {
+    let dir = TempDir::new("cooked");
+    let household = Household {
+        pantry_file: dir.path().join("pantry.yaml"),
+        ..Default::default()
+    };
+    let start = date("2022-08-07");
+    let meals = args(&["mon=chili"]);
+    plan_week_from_meals("./recipes", dir.dir(), &start, &7, &meals, &[], &household).unwrap();
+    assert!(!Plan::load(&start, "./recipes", dir.dir()).unwrap().cooked);
+
+    // Nothing is stocked, so there is no prompt to deduct from the pantry
+    mark_cooked("./recipes", dir.dir(), &start, &household).unwrap();
+    assert!(Plan::load(&start, "./recipes", dir.dir()).unwrap().cooked);
+    mark_cooked("./recipes", dir.dir(), &start, &household).unwrap();
+    assert!(!household.pantry_file.exists());
+}
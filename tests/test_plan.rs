@@ -0,0 +1,364 @@
+mod common;
+
+use averse::config::Household;
+use averse::errors::PlanError;
+use averse::plan::*;
+use chrono::Weekday;
+use common::{cook, date, TempDir};
+
+#[test]
+fn test_plan_week_from_meals() {
+    let dir = TempDir::new("plan-meals");
+    let plan_dir = dir.dir();
+
+    let start = date("2022-08-07");
+    let meals = vec![
+        "mon=chili".to_string(),
+        "2022-08-12 = chimichurri".to_string(),
+    ];
+    plan_week_from_meals(
+        "./recipes",
+        plan_dir,
+        &start,
+        &7,
+        &meals,
+        &[],
+        &Household::default(),
+    )
+    .unwrap();
+    let plan = Plan::load(&start, "./recipes", plan_dir).unwrap();
+    assert_eq!(
+        plan.recipes[&date("2022-08-08")][&Slot::Dinner],
+        cook("chili")
+    );
+    assert_eq!(
+        plan.recipes[&date("2022-08-12")][&Slot::Dinner],
+        cook("chimichurri")
+    );
+
+    let meals = vec!["Monday=not-a-recipe".to_string()];
+    let err = plan_week_from_meals(
+        "./recipes",
+        plan_dir,
+        &start,
+        &7,
+        &meals,
+        &[],
+        &Household::default(),
+    );
+    assert!(matches!(err, Err(PlanError::UnknownRecipe(_))));
+
+    let meals = vec!["Someday=chili".to_string()];
+    let err = plan_week_from_meals(
+        "./recipes",
+        plan_dir,
+        &start,
+        &7,
+        &meals,
+        &[],
+        &Household::default(),
+    );
+    assert!(matches!(err, Err(PlanError::InvalidDay(_))));
+
+    let meals = vec!["2022-08-20=chili".to_string()];
+    let err = plan_week_from_meals(
+        "./recipes",
+        plan_dir,
+        &start,
+        &7,
+        &meals,
+        &[],
+        &Household::default(),
+    );
+    assert!(matches!(err, Err(PlanError::InvalidDay(_))));
+
+    let meals = vec!["chili".to_string()];
+    let err = plan_week_from_meals(
+        "./recipes",
+        plan_dir,
+        &start,
+        &7,
+        &meals,
+        &[],
+        &Household::default(),
+    );
+    assert!(matches!(err, Err(PlanError::InvalidMeal(_))));
+}
+
+#[test]
+fn test_meal_slots() {
+    let dir = TempDir::new("plan-slots");
+    let plan_dir = dir.dir();
+
+    let start = date("2022-08-07");
+    let meals = vec![
+        "mon:breakfast=chimichurri".to_string(),
+        "mon = chili".to_string(),
+        "Monday:LUNCH=dill-pasta-salad".to_string(),
+    ];
+    plan_week_from_meals(
+        "./recipes",
+        plan_dir,
+        &start,
+        &7,
+        &meals,
+        &[],
+        &Household::default(),
+    )
+    .unwrap();
+    let plan = Plan::load(&start, "./recipes", plan_dir).unwrap();
+    let slots = plan
+        .meals()
+        .map(|(_, slot, meal)| (slot, meal.recipe().as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        slots,
+        vec![
+            (Slot::Breakfast, "chimichurri"),
+            (Slot::Lunch, "dill-pasta-salad"),
+            (Slot::Dinner, "chili"),
+        ]
+    );
+    assert_eq!(
+        used_slots(&[plan]),
+        vec![Slot::Breakfast, Slot::Lunch, Slot::Dinner]
+    );
+
+    let meals = vec!["mon:brunch=chili".to_string()];
+    let err = plan_week_from_meals(
+        "./recipes",
+        plan_dir,
+        &start,
+        &7,
+        &meals,
+        &[],
+        &Household::default(),
+    );
+    assert!(matches!(err, Err(PlanError::InvalidSlot(_))));
+
+    // Days may nest recipes under slots or list them for the default slot
+    let plan: Plan = serde_yaml::from_str(
+        "date: 2022-08-07\nrecipes:\n  mon: [chili]\n  tue:\n    snack: [chimichurri]\n",
+    )
+    .unwrap();
+    assert_eq!(
+        plan.recipes[&date("2022-08-08")][&Slot::Dinner],
+        cook("chili")
+    );
+    assert_eq!(
+        plan.recipes[&date("2022-08-09")][&Slot::Snack],
+        cook("chimichurri")
+    );
+}
+
+#[test]
+fn test_load_weekday_plan() {
+    // Older plans are keyed by weekday, relative to the plan date
+    let plan = Plan::load(&date("2022-07-15"), "./recipes", "./plans").unwrap();
+    assert_eq!(
+        plan.recipes[&date("2022-07-17")][&Slot::Dinner],
+        cook("veg-fruit-prep")
+    );
+    assert_eq!(
+        plan.recipes[&date("2022-07-18")][&Slot::Dinner],
+        cook("chimichurri")
+    );
+
+    let plan: Plan =
+        serde_yaml::from_str("name: 2022-07-24\nrecipes:\n  Tuedsay: [chili]\n").unwrap();
+    assert_eq!(
+        plan.recipes[&date("2022-07-26")][&Slot::Dinner],
+        cook("chili")
+    );
+    assert!(serde_yaml::from_str::<Plan>("name: 2022-15-22\nrecipes: {}\n").is_err());
+}
+
+#[test]
+fn test_plan_span() {
+    let dir = TempDir::new("plan-span");
+    let plan_dir = dir.dir();
+
+    // Thursday to Sunday
+    let start = date("2022-08-11");
+    let meals = vec!["sun=chili".to_string()];
+    plan_week_from_meals(
+        "./recipes",
+        plan_dir,
+        &start,
+        &4,
+        &meals,
+        &[],
+        &Household::default(),
+    )
+    .unwrap();
+    let plan = Plan::load(&start, "./recipes", plan_dir).unwrap();
+    assert_eq!(plan.end, date("2022-08-14"));
+    assert_eq!(plan.days().len(), 4);
+    assert_eq!(
+        plan.recipes[&date("2022-08-14")][&Slot::Dinner],
+        cook("chili")
+    );
+    assert_eq!(plan.title(), "2022-08-11 to 2022-08-14");
+
+    let meals = vec!["mon=chili".to_string()];
+    let err = plan_week_from_meals(
+        "./recipes",
+        plan_dir,
+        &start,
+        &4,
+        &meals,
+        &[],
+        &Household::default(),
+    );
+    assert!(matches!(err, Err(PlanError::InvalidDay(_))));
+    let err = plan_week_from_meals(
+        "./recipes",
+        plan_dir,
+        &start,
+        &0,
+        &meals,
+        &[],
+        &Household::default(),
+    );
+    assert!(matches!(err, Err(PlanError::InvalidSpan(..))));
+
+    // The Sunday wraps into a second row unless weeks start on Monday
+    let weeks = plan.weeks(Weekday::Sun);
+    assert_eq!(weeks.len(), 2);
+    assert_eq!(weeks[0].plan, Some(start));
+    assert_eq!(weeks[0].days[0], (date("2022-08-07"), None));
+    assert_eq!(weeks[1].plan, None);
+    let meals = weeks[1].days[0].1.as_ref().unwrap();
+    assert_eq!(meals[&Slot::Dinner], cook("chili"));
+    assert_eq!(plan.weeks(Weekday::Mon).len(), 1);
+}
+
+#[test]
+fn test_load_month_plan() {
+    let plan: Plan = serde_yaml::from_str(
+        "date: 2022-08-01\nend: 2022-08-31\nrecipes:\n  2022-08-30: [chili]\n",
+    )
+    .unwrap();
+    assert_eq!(plan.days().len(), 31);
+    assert_eq!(plan.weeks(Weekday::Sun).len(), 5);
+    assert!(serde_yaml::from_str::<Plan>("date: 2022-08-01\nend: 2022-07-01\n").is_err());
+
+    // Older plans always cover a week
+    let plan = Plan::load(&date("2022-07-15"), "./recipes", "./plans").unwrap();
+    assert_eq!(plan.end, date("2022-07-21"));
+}
+
+#[test]
+fn test_leftovers() {
+    let dir = TempDir::new("plan-leftovers");
+    let plan_dir = dir.dir();
+
+    let start = date("2022-08-07");
+    let meals = vec!["sun=chili".to_string()];
+    let leftovers = vec!["mon=chili".to_string(), "tue:lunch=chili".to_string()];
+    plan_week_from_meals(
+        "./recipes",
+        plan_dir,
+        &start,
+        &7,
+        &meals,
+        &leftovers,
+        &Household::default(),
+    )
+    .unwrap();
+    let mut plan = Plan::load(&start, "./recipes", plan_dir).unwrap();
+    let leftovers = Meal::Leftovers {
+        leftovers: "chili".into(),
+    };
+    assert_eq!(
+        plan.recipes[&date("2022-08-08")][&Slot::Dinner],
+        vec![leftovers.clone()]
+    );
+    assert_eq!(
+        plan.recipes[&date("2022-08-09")][&Slot::Lunch],
+        vec![leftovers]
+    );
+
+    // Groceries are only counted for the cook, while each cook adds up
+    let groceries = plan.compile_groceries().unwrap().groceries();
+    assert!(groceries
+        .iter()
+        .any(|x| x.to_string() == "4 Can kidney beans"));
+    let meals = vec!["sun=chili".to_string(), "mon=chili".to_string()];
+    plan_week_from_meals(
+        "./recipes",
+        plan_dir,
+        &start,
+        &7,
+        &meals,
+        &[],
+        &Household::default(),
+    )
+    .unwrap();
+    let mut plan = Plan::load(&start, "./recipes", plan_dir).unwrap();
+    let groceries = plan.compile_groceries().unwrap().groceries();
+    assert!(groceries
+        .iter()
+        .any(|x| x.to_string() == "8 Can kidney beans"));
+
+    let err = plan_week_from_meals(
+        "./recipes",
+        plan_dir,
+        &start,
+        &7,
+        &["tue=chili".to_string()],
+        &["mon=chili".to_string()],
+        &Household::default(),
+    );
+    assert!(matches!(err, Err(PlanError::NoCook(..))));
+
+    assert_eq!(leftover_days(6, 2), 2);
+    assert_eq!(leftover_days(4, 4), 0);
+    assert_eq!(leftover_days(1, 2), 0);
+}
+
+#[test]
+fn test_edit_plan() {
+    let dir = TempDir::new("plan-edit");
+    let plan_dir = dir.dir();
+
+    let (sun, mon, tue) = (date("2022-08-07"), date("2022-08-08"), date("2022-08-09"));
+    let meals = vec!["sun=chili".to_string(), "sun=chimichurri".to_string()];
+    let leftovers = vec!["mon=chili".to_string()];
+    plan_week_from_meals(
+        "./recipes",
+        plan_dir,
+        &sun,
+        &7,
+        &meals,
+        &leftovers,
+        &Household::default(),
+    )
+    .unwrap();
+    let mut original = Plan::load(&sun, "./recipes", plan_dir).unwrap();
+    original.compile_groceries().unwrap();
+
+    let mut plan = original.clone();
+    plan.move_meal(sun, Slot::Dinner, 1, tue);
+    assert_eq!(plan.recipes[&sun][&Slot::Dinner], cook("chili"));
+    assert_eq!(plan.recipes[&tue][&Slot::Dinner], cook("chimichurri"));
+
+    plan.swap_days(mon, tue);
+    assert_eq!(plan.recipes[&mon][&Slot::Dinner], cook("chimichurri"));
+    assert!(plan.recipes[&tue][&Slot::Dinner][0].is_leftovers());
+
+    let removed = plan.remove_meal(mon, Slot::Dinner, 0);
+    assert_eq!(removed, Some(Meal::Cook("chimichurri".into())));
+    assert!(!plan.recipes.contains_key(&mon));
+    assert_eq!(plan.remove_meal(mon, Slot::Dinner, 0), None);
+
+    plan.replace_meal(sun, Slot::Dinner, 0, Meal::Cook("cast-iron-pizza".into()));
+    plan.compile_groceries().unwrap();
+    let changes = original.diff(&plan);
+    assert!(changes.contains(&"- Sun 2022-08-07 Dinner: chili".to_string()));
+    assert!(changes.contains(&"+ Sun 2022-08-07 Dinner: cast-iron-pizza".to_string()));
+    assert!(changes.contains(&"- Mon 2022-08-08 Dinner: chili (leftovers)".to_string()));
+    assert!(changes.contains(&"+ Tue 2022-08-09 Dinner: chili (leftovers)".to_string()));
+    assert!(changes.contains(&"- Grocery: 4 Can kidney beans".to_string()));
+    assert!(original.diff(&original).is_empty());
+}
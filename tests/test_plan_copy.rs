@@ -0,0 +1,81 @@
+//! Copying plans in JSON mode, which skips the review prompts. JSON mode is process-wide,
+//! so these tests have their own binary
+mod common;
+
+use averse::config::Household;
+use averse::errors::PlanError;
+use averse::plan::*;
+use averse::utils::{get_plan_path, set_json_output};
+use common::{cook, date, TempDir};
+use std::fs;
+
+#[test]
+fn test_plan_week_from_copy() {
+    set_json_output(true);
+    let dir = TempDir::new("plan-copy");
+    let plan_dir = dir.dir();
+
+    let source = date("2022-07-24");
+    let meals = vec!["mon=chili".to_string()];
+    let leftovers = vec!["tue:lunch=chili".to_string()];
+    plan_week_from_meals(
+        "./recipes",
+        plan_dir,
+        &source,
+        &7,
+        &meals,
+        &leftovers,
+        &Household::default(),
+    )
+    .unwrap();
+    let original = fs::read_to_string(get_plan_path(plan_dir, "2022-07-24")).unwrap();
+
+    let start = date("2022-08-07");
+    let query = "".parse().unwrap();
+    plan_week_from_copy(
+        "./recipes",
+        plan_dir,
+        &source,
+        &start,
+        &None,
+        &query,
+        &Household::default(),
+    )
+    .unwrap();
+    let plan = Plan::load(&start, "./recipes", plan_dir).unwrap();
+    assert_eq!(plan.end, date("2022-08-13"));
+    assert_eq!(
+        plan.recipes[&date("2022-08-08")][&Slot::Dinner],
+        cook("chili")
+    );
+    assert!(plan.recipes[&date("2022-08-09")][&Slot::Lunch][0].is_leftovers());
+    assert_eq!(
+        fs::read_to_string(get_plan_path(plan_dir, "2022-07-24")).unwrap(),
+        original
+    );
+
+    // Meals past the end of a shorter copy are dropped
+    plan_week_from_copy(
+        "./recipes",
+        plan_dir,
+        &source,
+        &start,
+        &Some(2),
+        &query,
+        &Household::default(),
+    )
+    .unwrap();
+    let plan = Plan::load(&start, "./recipes", plan_dir).unwrap();
+    assert_eq!(plan.meals().count(), 1);
+
+    let err = plan_week_from_copy(
+        "./recipes",
+        plan_dir,
+        &source,
+        &source,
+        &None,
+        &query,
+        &Household::default(),
+    );
+    assert!(matches!(err, Err(PlanError::CopyOntoSelf(_))));
+}
@@ -0,0 +1,33 @@
+use averse::category::Categories;
+use averse::plan::Plan;
+use averse::print::*;
+use chrono::NaiveDate;
+
+#[test]
+fn test_plan_to_text() {
+    let mut plan = Plan::load(
+        &NaiveDate::from_ymd_opt(2022, 7, 24).unwrap(),
+        "./recipes",
+        "./plans",
+    )
+    .unwrap();
+    plan.compile_groceries().unwrap();
+    let sheet = plan_to_text(&plan, &Categories::default());
+    assert!(sheet.lines().all(|line| line.chars().count() <= 80));
+    assert!(sheet.contains("chimichurri"));
+    assert!(sheet.contains("[ ] 8 Oz rigatoni pasta"));
+}
+
+#[test]
+fn test_plan_to_html() {
+    let mut plan = Plan::load(
+        &NaiveDate::from_ymd_opt(2022, 7, 24).unwrap(),
+        "./recipes",
+        "./plans",
+    )
+    .unwrap();
+    plan.compile_groceries().unwrap();
+    let html = plan_to_html(&plan, &Categories::default());
+    assert!(html.contains("<h1>Week of 2022-07-24</h1>"));
+    assert!(html.contains("<h3>Produce</h3>"));
+}
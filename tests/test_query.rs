@@ -0,0 +1,44 @@
+use averse::query::*;
+use averse::utils::get_recipe_out_path;
+use averse::Recipe;
+use std::str::FromStr;
+
+#[test]
+fn test_parse_query() {
+    let query = Query::from_str(r#"tag:soup ingredient:chicken -tag:dessert "slow cooker""#);
+    assert_eq!(
+        query.unwrap(),
+        Query::And(vec![
+            Query::Term(Term::Tag("soup".into())),
+            Query::Term(Term::Ingredient("chicken".into())),
+            Query::Not(Box::new(Query::Term(Term::Tag("dessert".into())))),
+            Query::Term(Term::Text("slow cooker".into())),
+        ])
+    );
+    let query = Query::from_str("name:chili OR step:\"instant pot\"").unwrap();
+    assert_eq!(
+        query,
+        Query::Or(vec![
+            Query::Term(Term::Name("chili".into())),
+            Query::Term(Term::Step("instant pot".into())),
+        ])
+    );
+    assert_eq!(Query::from_str("  ").unwrap(), Query::default());
+    assert!(Query::from_str("tag:\"soup").is_err());
+    assert!(Query::from_str("color:red").is_err());
+    assert!(Query::from_str("tag:").is_err());
+    assert!(Query::from_str("OR chili").is_err());
+    assert!(Query::from_str("chili OR").is_err());
+}
+
+#[test]
+fn test_query_matches() {
+    let chili = Recipe::try_from(&get_recipe_out_path("./recipes", "chili")).unwrap();
+    let matches = |q: &str| Query::from_str(q).unwrap().matches(&chili);
+    assert!(matches(""));
+    assert!(matches("tag:\"Instant Pot\" ingredient:beef"));
+    assert!(matches("\"natural release\""));
+    assert!(matches("tag:dessert OR name:chili"));
+    assert!(!matches("-tag:chili"));
+    assert!(!matches("step:oven"));
+}
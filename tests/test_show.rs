@@ -0,0 +1,52 @@
+mod common;
+
+use averse::errors::{ErrorCategory, ShowError};
+use averse::show::{resolve_recipe, show_recipe};
+use common::TempDir;
+use std::fs;
+
+#[test]
+fn test_resolve_recipe() {
+    let slug = |x: &str| resolve_recipe("./recipes", x).unwrap().slug();
+    assert_eq!(slug("chili"), "chili");
+    assert_eq!(slug("Bagel Breakfast"), "bagel-breakfast");
+    assert_eq!(slug("cast-iron"), "cast-iron-pizza");
+    assert!(matches!(
+        resolve_recipe("./recipes", "chicken"),
+        Err(ShowError::Ambiguous(_, _))
+    ));
+    match resolve_recipe("./recipes", "chilli") {
+        Err(ShowError::NotFound(_, suggestions)) => assert!(suggestions.contains(&"chili".into())),
+        _ => panic!("Expected suggestions for a misspelled recipe"),
+    }
+}
+
+#[test]
+fn test_show_recipe_scale() {
+    let show = |recipe_dir: &str, name: &str, servings: Option<u16>, scale: Option<f32>| {
+        show_recipe(recipe_dir, name, &true, &false, &servings, &scale)
+    };
+    show("./recipes", "chili", Some(2), None).unwrap();
+    show("./recipes", "chili", None, Some(0.5)).unwrap();
+    for scale in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+        match show("./recipes", "chili", None, Some(scale)) {
+            Err(e @ ShowError::InvalidScale(_)) => assert_eq!(e.category(), ErrorCategory::Input),
+            x => panic!("Expected an invalid scale for {scale}, got {x:?}"),
+        }
+    }
+    assert!(matches!(
+        show("./recipes", "chili", Some(0), None),
+        Err(ShowError::InvalidScale(_))
+    ));
+
+    let dir = TempDir::new("show");
+    fs::write(
+        dir.path().join("toast.yaml"),
+        "name: toast\ntags: []\nservings: 0\ningredients: [{name: bread, amount: 1, unit: Item}]\nsteps: []\n",
+    )
+    .unwrap();
+    assert!(matches!(
+        show(dir.dir(), "toast", Some(2), None),
+        Err(ShowError::NoServings(_))
+    ));
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use averse::config::Household;
+use averse::errors::{PlanError, RecipeParsingError, TemplateError};
+use averse::plan::*;
+use averse::template::*;
+use common::{date, TempDir};
+
+#[test]
+fn test_entries() {
+    let template = Template::load("./templates", "weeknight-rotation").unwrap();
+    let entries = template.entries().unwrap();
+    let open = entries.iter().filter(|x| x.2 == Entry::Open).count();
+    let queries = entries
+        .iter()
+        .filter(|x| matches!(x.2, Entry::Query(_)))
+        .count();
+    assert_eq!((open, queries), (2, 2));
+    assert!(entries.contains(&(
+        &"friday".to_string(),
+        Slot::Dinner,
+        Entry::Meal(Meal::Cook("cast-iron-pizza".into()))
+    )));
+}
+
+#[test]
+fn test_plan_week_from_template() {
+    let dir = TempDir::new("template");
+    let plan_dir = dir.dir();
+
+    let template: Template = serde_yaml::from_str(
+        "
+        days: 10
+        recipes:
+          monday:
+            dinner: [chili]
+          tuesday:
+            dinner: [leftovers: chili]
+          +8:
+            lunch: [tag:pizza]
+        notes:
+          monday: Soak beans
+        ",
+    )
+    .unwrap();
+    let start = date("2022-08-07");
+    plan_week_from_template(
+        "./recipes",
+        plan_dir,
+        &start,
+        &None,
+        &"".parse().unwrap(),
+        &template,
+        &Household::default(),
+    )
+    .unwrap();
+    let plan = Plan::load(&start, "./recipes", plan_dir).unwrap();
+    assert_eq!(plan.end, date("2022-08-16"));
+    assert_eq!(
+        plan.recipes[&date("2022-08-15")][&Slot::Lunch],
+        vec![Meal::Cook("cast-iron-pizza".into())]
+    );
+    assert!(plan.recipes[&date("2022-08-09")][&Slot::Dinner][0].is_leftovers());
+    assert_eq!(plan.notes[&date("2022-08-08")], "Soak beans");
+
+    let saved = Template::from_plan(&plan);
+    assert_eq!(saved.days, Some(10));
+    assert!(saved.recipes.contains_key("+1"));
+    assert!(saved.recipes.contains_key("+8"));
+
+    let template: Template =
+        serde_yaml::from_str("recipes: {monday: {dinner: [tag:no-such-tag]}}").unwrap();
+    let err = plan_week_from_template(
+        "./recipes",
+        plan_dir,
+        &start,
+        &None,
+        &"".parse().unwrap(),
+        &template,
+        &Household::default(),
+    );
+    assert!(matches!(err, Err(PlanError::NoMatch(..))));
+
+    let template_dir = dir.path().join("templates");
+    let template_dir = template_dir.to_str().unwrap();
+    let err = save_template(
+        "./recipes",
+        plan_dir,
+        template_dir,
+        "../escaped",
+        &start,
+        &false,
+    );
+    assert!(matches!(
+        err,
+        Err(TemplateError::RecipeError(RecipeParsingError::InvalidName(
+            _
+        )))
+    ));
+    assert!(!dir.path().join("escaped.yaml").exists());
+    assert!(matches!(
+        Template::load(template_dir, "../2022-08-07"),
+        Err(RecipeParsingError::InvalidName(_))
+    ));
+}
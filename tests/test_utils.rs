@@ -4,6 +4,7 @@ use tabled::Tabled;
 
 #[test]
 fn test_table() {
+    #[allow(non_snake_case)]
     #[derive(Tabled)]
     struct Foo {
         Col1: String,